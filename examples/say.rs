@@ -1,22 +1,29 @@
 use rodio::{OutputStream, Sink};
 use rodio::buffer::SamplesBuffer;
 
-use rustsam::reciter;
-use rustsam::parser;
-use rustsam::renderer;
+use rustsam::Sam;
+use rustsam::renderer::Voice;
 
 fn main() {
     let text = "test";
 
-    let phrase = reciter::text_to_phonemes(text).expect("Could not recite text");
-    let phonemes = parser::parse_phonemes(&phrase).expect("Could not parse phonemes");
-    let output = renderer::render(&phonemes, 100, 127, 127, 80, false);
+    let voice = Voice::builder()
+        .pitch(100)
+        .speed(80)
+        .mouth(127)
+        .throat(127)
+        .build()
+        .expect("Could not create voice");
 
-    //std::fs::write("/tmp/output.raw", &output).expect("Could not write output file");
+    let sample_rate = voice.sample_rate();
+    let output = Sam::new(voice).speak_samples::<i16>(text).expect("Could not speak text");
+
+    //let file = std::fs::File::create("/tmp/output.wav").expect("Could not create output file");
+    //rustsam::wav::write_wav(file, &output, sample_rate, rustsam::wav::Format::Signed16).expect("Could not write output file");
 
     // Play audio file
     let (_, stream_handle) = OutputStream::try_default().expect("Could not open audio device");
     let sink = Sink::try_new(&stream_handle).expect("Could not create audio sink");
-    sink.append(SamplesBuffer::new(1, 22050, output.into_iter().map(|sample| sample as u16 * 256).collect::<Vec<_>>()));
+    sink.append(SamplesBuffer::new(1, sample_rate, output));
     sink.sleep_until_end();
 }
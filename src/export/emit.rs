@@ -0,0 +1,300 @@
+use std::io::{self, Write};
+
+use crate::wav;
+
+/// The magic bytes at the start of a binary file.
+pub const MAGIC: [u8; 3] = *b"SAM";
+
+/// The size of the header of a binary file.
+pub const HEADER_SIZE: usize = 12;
+
+// Set in the format byte of the header when the data is compressed
+const RLE_FLAG: u8 = 0x80;
+
+// The longest literal and run a control byte of the RLE data can describe
+const MAX_LITERAL: usize = 128;
+const MIN_RUN: usize = 3;
+const MAX_RUN: usize = MIN_RUN + 127;
+
+// Bytes on each db or DATA line, which keeps DATA lines well below the 255 characters MSX-BASIC
+// accepts
+const BYTES_PER_LINE: usize = 16;
+
+// MSX-BASIC line numbers
+const LINE_STEP: u32 = 10;
+const MAX_LINE: u32 = 65529;
+
+/// What the emitted data holds.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Content {
+    /// Samples in one of the sample formats of WAV files.
+    Samples(wav::Format),
+
+    /// 4 bit samples packed two to a byte, as written by [`super::pcm::pack`].
+    Packed4,
+
+    /// PSG registers, as written by [`super::psg::write_registers`].
+    Psg,
+
+    /// SCC waveforms and registers, as written by [`super::scc::Dump::write`].
+    Scc
+}
+
+impl Content {
+    /// The number identifying the content in the header of a binary file.
+    pub fn id(&self) -> u8 {
+        match self {
+            Content::Samples(wav::Format::Unsigned8) => 0,
+            Content::Samples(wav::Format::Signed16) => 1,
+            Content::Samples(wav::Format::Float32) => 2,
+            Content::Packed4 => 3,
+            Content::Psg => 4,
+            Content::Scc => 5
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            Content::Samples(wav::Format::Unsigned8) => "unsigned 8 bit PCM",
+            Content::Samples(wav::Format::Signed16) => "signed 16 bit PCM",
+            Content::Samples(wav::Format::Float32) => "32 bit float PCM",
+            Content::Packed4 => "packed 4 bit PCM",
+            Content::Psg => "PSG registers",
+            Content::Scc => "SCC registers"
+        }
+    }
+}
+
+/// How the emitted data is compressed.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Compression {
+    #[default]
+    None,
+
+    /// Run length encoding, see [`rle_compress`].
+    Rle
+}
+
+/// Describes the data written by the emitters.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Info {
+    pub content: Content,
+
+    /// The sample rate for samples, or the interrupt rate for registers, in Hz.
+    pub rate: u32,
+
+    pub compression: Compression
+}
+
+impl Info {
+    // The data as it is emitted
+    fn encode(&self, data: &[u8]) -> Vec<u8> {
+        match self.compression {
+            Compression::None => data.to_vec(),
+            Compression::Rle => rle_compress(data)
+        }
+    }
+
+    fn description(&self, length: usize, encoded: usize) -> String {
+        let mut description = format!("{} at {} Hz, {} bytes", self.content.description(), self.rate, length);
+
+        if self.compression == Compression::Rle {
+            description += &format!(", RLE compressed to {} bytes", encoded);
+        }
+
+        description
+    }
+}
+
+/// Compress data with a run length encoding that is simple to decode on a Z80. Each block starts
+/// with a control byte. A control byte `n` below 128 is followed by `n + 1` bytes to copy, and a
+/// control byte `n` of 128 and up by a single byte to repeat `n - 125` times. The data has no end
+/// marker, the decoder stops after the uncompressed length.
+pub fn rle_compress(data: &[u8]) -> Vec<u8> {
+    let mut compressed = Vec::new();
+    let mut literal_start = 0;
+    let mut position = 0;
+
+    let flush = |compressed: &mut Vec<u8>, literal: &[u8]| {
+        for chunk in literal.chunks(MAX_LITERAL) {
+            compressed.push(chunk.len() as u8 - 1);
+            compressed.extend_from_slice(chunk);
+        }
+    };
+
+    while position < data.len() {
+        let run = data[position..].iter()
+            .take(MAX_RUN)
+            .take_while(|byte| **byte == data[position])
+            .count();
+
+        if run < MIN_RUN {
+            position += 1;
+            continue;
+        }
+
+        flush(&mut compressed, &data[literal_start..position]);
+        compressed.extend([0x80 | (run - MIN_RUN) as u8, data[position]]);
+
+        position += run;
+        literal_start = position;
+    }
+
+    flush(&mut compressed, &data[literal_start..]);
+    compressed
+}
+
+/// Decompress data compressed by [`rle_compress`], or `None` when a block is cut off.
+pub fn rle_decompress(compressed: &[u8]) -> Option<Vec<u8>> {
+    let mut data = Vec::new();
+    let mut position = 0;
+
+    while let Some(&control) = compressed.get(position) {
+        if control & 0x80 == 0 {
+            let end = position + 1 + control as usize + 1;
+            data.extend_from_slice(compressed.get(position + 1..end)?);
+            position = end;
+        } else {
+            let byte = *compressed.get(position + 1)?;
+            data.extend(std::iter::repeat_n(byte, (control & 0x7F) as usize + MIN_RUN));
+            position += 2;
+        }
+    }
+
+    Some(data)
+}
+
+/// Write the data as a binary file with a header of [`HEADER_SIZE`] bytes: the [`MAGIC`], the
+/// format byte, and the rate and uncompressed length as little endian 32 bit numbers. The low bits
+/// of the format byte hold the [`Content::id`], and bit 7 is set when the data is RLE compressed.
+pub fn write_binary<W: Write>(mut writer: W, data: &[u8], info: &Info) -> io::Result<()> {
+    let mut format = info.content.id();
+    if info.compression == Compression::Rle {
+        format |= RLE_FLAG;
+    }
+
+    writer.write_all(&MAGIC)?;
+    writer.write_all(&[format])?;
+    writer.write_all(&info.rate.to_le_bytes())?;
+    writer.write_all(&(data.len() as u32).to_le_bytes())?;
+    writer.write_all(&info.encode(data))
+}
+
+/// Write the data as an include file for sjasmplus or tniASM, with `db` lines after the label.
+/// The uncompressed length and the rate are defined as `<label>_length` and `<label>_rate`.
+pub fn write_db<W: Write>(mut writer: W, data: &[u8], info: &Info, label: &str) -> io::Result<()> {
+    let encoded = info.encode(data);
+
+    writeln!(writer, "; {}", info.description(data.len(), encoded.len()))?;
+    writeln!(writer, "{}_length: equ {}", label, data.len())?;
+    writeln!(writer, "{}_rate: equ {}", label, info.rate)?;
+    writeln!(writer, "{}:", label)?;
+
+    for line in encoded.chunks(BYTES_PER_LINE) {
+        let bytes: Vec<String> = line.iter().map(|byte| format!("${:02X}", byte)).collect();
+        writeln!(writer, "    db {}", bytes.join(","))?;
+    }
+
+    Ok(())
+}
+
+/// Write the data as MSX-BASIC `DATA` lines, numbered in steps of 10 after a `REM` line at the
+/// first line number. Fails when the line numbers would go past 65529.
+pub fn write_basic<W: Write>(mut writer: W, data: &[u8], info: &Info, first_line: u16) -> io::Result<()> {
+    let encoded = info.encode(data);
+    let last_line = first_line as u32 + encoded.len().div_ceil(BYTES_PER_LINE) as u32 * LINE_STEP;
+
+    if last_line > MAX_LINE {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "Too much data for the MSX-BASIC line numbers"));
+    }
+
+    writeln!(writer, "{} REM {}", first_line, info.description(data.len(), encoded.len()))?;
+
+    for (index, line) in encoded.chunks(BYTES_PER_LINE).enumerate() {
+        let bytes: Vec<String> = line.iter().map(|byte| byte.to_string()).collect();
+        writeln!(writer, "{} DATA {}", first_line as u32 + (index as u32 + 1) * LINE_STEP, bytes.join(","))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INFO: Info = Info {
+        content: Content::Packed4,
+        rate: 8000,
+        compression: Compression::None
+    };
+
+    const RLE: Info = Info {
+        compression: Compression::Rle,
+        ..INFO
+    };
+
+    #[test]
+    fn rle() {
+        assert!(rle_compress(&[]).is_empty());
+        assert_eq!(rle_compress(&[1, 2, 2]), [2, 1, 2, 2]);
+        assert_eq!(rle_compress(&[1, 2, 2, 2, 2, 3]), [0, 1, 0x81, 2, 0, 3]);
+        assert_eq!(rle_compress(&[7; 131]), [0xFF, 7, 0, 7]);
+
+        let data: Vec<u8> = (0..1000).map(|index| if index % 300 < 100 { 0x80 } else { (index * 7) as u8 }).collect();
+        let compressed = rle_compress(&data);
+        assert!(compressed.len() < data.len());
+        assert_eq!(rle_decompress(&compressed), Some(data));
+
+        assert_eq!(rle_decompress(&[3, 1, 2]), None);
+        assert_eq!(rle_decompress(&[0x80]), None);
+    }
+
+    #[test]
+    fn binary() {
+        let mut file = Vec::new();
+        write_binary(&mut file, &[1, 2, 3], &INFO).unwrap();
+        assert_eq!(file, [b'S', b'A', b'M', 3, 0x40, 0x1F, 0, 0, 3, 0, 0, 0, 1, 2, 3]);
+
+        let mut file = Vec::new();
+        write_binary(&mut file, &[0; 10], &Info { content: Content::Psg, rate: 50, compression: Compression::Rle }).unwrap();
+        assert_eq!(file[3], 0x84);
+        assert_eq!(&file[8..HEADER_SIZE], [10, 0, 0, 0]);
+        assert_eq!(&file[HEADER_SIZE..], [0x87, 0]);
+    }
+
+    #[test]
+    fn db() {
+        let mut file = Vec::new();
+        write_db(&mut file, &(0..20).collect::<Vec<u8>>(), &INFO, "hello").unwrap();
+
+        let text = String::from_utf8(file).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "; packed 4 bit PCM at 8000 Hz, 20 bytes");
+        assert_eq!(lines[1], "hello_length: equ 20");
+        assert_eq!(lines[2], "hello_rate: equ 8000");
+        assert_eq!(lines[3], "hello:");
+        assert_eq!(lines[4], "    db $00,$01,$02,$03,$04,$05,$06,$07,$08,$09,$0A,$0B,$0C,$0D,$0E,$0F");
+        assert_eq!(lines[5], "    db $10,$11,$12,$13");
+        assert_eq!(lines.len(), 6);
+
+        let mut file = Vec::new();
+        write_db(&mut file, &[255; 5], &RLE, "hello").unwrap();
+        assert!(String::from_utf8(file).unwrap().ends_with("RLE compressed to 2 bytes\nhello_length: equ 5\nhello_rate: equ 8000\nhello:\n    db $82,$FF\n"));
+    }
+
+    #[test]
+    fn basic() {
+        let mut file = Vec::new();
+        write_basic(&mut file, &[255; 17], &INFO, 1000).unwrap();
+
+        let text = String::from_utf8(file).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "1000 REM packed 4 bit PCM at 8000 Hz, 17 bytes");
+        assert_eq!(lines[1], format!("1010 DATA {}", ["255"; 16].join(",")));
+        assert_eq!(lines[2], "1020 DATA 255");
+        assert!(lines.iter().all(|line| line.len() < 255));
+
+        assert!(write_basic(io::sink(), &[0; 16 * 10], &INFO, 65430).is_err());
+        assert!(write_basic(io::sink(), &[0; 16 * 10], &RLE, 65430).is_ok());
+    }
+}
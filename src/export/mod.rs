@@ -0,0 +1,53 @@
+use crate::parser::Phoneme;
+use crate::renderer::{self, Frame, Voice};
+
+pub mod emit;
+pub mod pcm;
+pub mod psg;
+pub mod scc;
+pub mod vgm;
+
+/// The CPU clock of an MSX in Hz, which also clocks the SCC. The PSG runs at half of it.
+pub const MSX_CLOCK: f64 = 3_579_545.0;
+
+/// The highest interrupt rate of a player in Hz. Frame rates in the settings of the exports are
+/// clamped to the range from 1 up to this.
+pub const MAX_FRAME_RATE: u32 = 1000;
+
+// The frames the renderer plays for the phonemes
+fn frames(phonemes: &[Phoneme], voice: &Voice) -> Vec<Frame> {
+    if phonemes.is_empty() {
+        return Vec::new();
+    }
+
+    let mut prepared_frames = renderer::prepare_frames(phonemes, voice.pitch(), voice.mouth(), voice.throat(), voice.sing_mode(), voice.compatibility());
+    prepared_frames.frames.truncate(prepared_frames.frame_count);
+    prepared_frames.frames
+}
+
+// Group the frames by the interrupt that plays them when a player runs at the frame rate. Every
+// interrupt gets at least one frame, so a frame may be repeated when the rate is higher than that
+// of the renderer.
+fn interrupts(frames: &[Frame], speed: u8, frame_rate: u32) -> impl Iterator<Item = &[Frame]> {
+    let frames_per_interrupt = renderer::frame_rate(speed) / clamp_frame_rate(frame_rate) as f64;
+    let count = (frames.len() as f64 / frames_per_interrupt).ceil() as usize;
+
+    (0..count).map(move |interrupt| {
+        let start = ((interrupt as f64 * frames_per_interrupt) as usize).min(frames.len() - 1);
+        let end = (((interrupt + 1) as f64 * frames_per_interrupt) as usize).clamp(start + 1, frames.len());
+
+        &frames[start..end]
+    })
+}
+
+// The frame rate of the settings within the supported range
+fn clamp_frame_rate(frame_rate: u32) -> u32 {
+    frame_rate.clamp(1, MAX_FRAME_RATE)
+}
+
+// The mean of a frame value over a group of frames
+fn mean(frames: &[Frame], value: impl Fn(&Frame) -> u8) -> u8 {
+    let sum: usize = frames.iter().map(|frame| value(frame) as usize).sum();
+
+    ((sum + frames.len() / 2) / frames.len()) as u8
+}
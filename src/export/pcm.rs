@@ -0,0 +1,119 @@
+use super::psg::LEVELS;
+use crate::renderer::Sample;
+
+/// How the 4 bit values map to output levels.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Curve {
+    /// Evenly spaced levels, like the volume register of the C64 SID.
+    #[default]
+    Linear,
+
+    /// The logarithmic volume levels of the AY-3-8910.
+    Ay
+}
+
+impl Curve {
+    /// The output level of a 4 bit value, from 0 to 1.
+    pub fn level(&self, value: u8) -> f64 {
+        match self {
+            Curve::Linear => (value & 0x0F) as f64 / 15.0,
+            Curve::Ay => LEVELS[(value & 0x0F) as usize]
+        }
+    }
+
+    // The value with the level closest to the target level
+    fn nearest(&self, level: f64) -> u8 {
+        match self {
+            Curve::Linear => (level * 15.0).round().clamp(0.0, 15.0) as u8,
+            Curve::Ay => (0..16)
+                .min_by(|a, b| (self.level(*a) - level).abs().total_cmp(&(self.level(*b) - level).abs()))
+                .unwrap_or(0)
+        }
+    }
+}
+
+/// How to quantize samples to 4 bits.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Settings {
+    pub curve: Curve,
+
+    /// Carry the quantization error over to the next sample, which trades the distortion of the
+    /// coarse levels for noise.
+    pub dither: bool
+}
+
+/// Quantize samples to 4 bit values from 0 to 15, for playback by writing them to a volume
+/// register at the sample rate.
+pub fn quantize<S: Sample>(samples: &[S], settings: &Settings) -> Vec<u8> {
+    let mut error = 0.0;
+
+    samples.iter().map(|sample| {
+        // Samples beyond full scale clip, so their error is not carried over
+        let level = ((sample.to_float() as f64 + 1.0) / 2.0).clamp(0.0, 1.0);
+        let target = if settings.dither { level + error } else { level };
+
+        let value = settings.curve.nearest(target);
+        error = target - settings.curve.level(value);
+
+        value
+    }).collect()
+}
+
+/// Pack 4 bit values two to a byte, with the first one in the high nibble. An odd number of
+/// values is padded by repeating the last one.
+pub fn pack(values: &[u8]) -> Vec<u8> {
+    values.chunks(2)
+        .map(|pair| (pair[0] & 0x0F) << 4 | (pair[pair.len() - 1] & 0x0F))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn curves() {
+        let linear = Settings::default();
+        let ay = Settings {
+            curve: Curve::Ay,
+            ..Settings::default()
+        };
+
+        assert_eq!(quantize(&[0x00_u8, 0x80, 0xFF], &linear), [0, 8, 15]);
+        assert_eq!(quantize(&[-1.0_f32, 0.0, 1.0], &ay), [0, 11, 15]);
+
+        for value in 1..16 {
+            assert!(Curve::Ay.level(value) > Curve::Ay.level(value - 1));
+            assert_eq!(Curve::Ay.nearest(Curve::Ay.level(value)), value);
+        }
+    }
+
+    #[test]
+    fn dither() {
+        let samples = [-0.3_f32; 100];
+
+        for curve in [Curve::Linear, Curve::Ay] {
+            let plain = quantize(&samples, &Settings { curve, dither: false });
+            let dithered = quantize(&samples, &Settings { curve, dither: true });
+
+            // Without dithering every sample gets the same value, with dithering the values
+            // average out to the input level
+            assert!(plain.iter().all(|value| *value == plain[0]));
+
+            let mean = dithered.iter().map(|value| curve.level(*value)).sum::<f64>() / dithered.len() as f64;
+            assert!((mean - 0.35).abs() < 0.01, "{:?} {}", curve, mean);
+
+            // Clipped samples do not build up an error that outlasts them
+            let samples: Vec<f32> = [1.5; 1000].into_iter().chain([-0.5; 100]).collect();
+            let dithered = quantize(&samples, &Settings { curve, dither: true });
+            assert!(dithered[1000..].iter().all(|value| *value < 15), "{:?}", curve);
+        }
+    }
+
+    #[test]
+    fn packing() {
+        assert_eq!(pack(&[1, 2, 3, 4]), [0x12, 0x34]);
+        assert_eq!(pack(&[15, 0, 7]), [0xF0, 0x77]);
+        assert!(pack(&[]).is_empty());
+    }
+}
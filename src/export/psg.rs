@@ -0,0 +1,203 @@
+use std::io::{self, Write};
+
+use super::{frames, interrupts, mean, MSX_CLOCK};
+use crate::parser::Phoneme;
+use crate::renderer::{self, Frame, Voice};
+
+/// The number of registers written for each interrupt, R0 up to R13.
+pub const REGISTER_COUNT: usize = 14;
+
+/// The values of the PSG registers R0 up to R13 for one interrupt.
+pub type Registers = [u8; REGISTER_COUNT];
+
+/// The relative output level of each volume of the AY-3-8910, which is roughly logarithmic.
+pub const LEVELS: [f64; 16] = [
+    0.0, 0.0137, 0.0205, 0.0291, 0.0423, 0.0618, 0.0847, 0.1369,
+    0.1691, 0.2647, 0.3527, 0.4499, 0.5704, 0.6873, 0.8482, 1.0
+];
+
+// The noise periods of the sampled consonants, in the order of the sample table: coronal,
+// palato-alveolar, labiodental, palatal and glottal
+const NOISE_PERIODS: [u8; 5] = [1, 3, 6, 12, 20];
+
+// The volume of the noise for unvoiced consonants, and mixed into channel C for voiced ones
+const UNVOICED_VOLUME: u8 = 12;
+const VOICED_VOLUME: u8 = 9;
+
+// Keeps I/O port A an input and port B an output, as the MSX requires
+const MIXER_PORTS: u8 = 0b1000_0000;
+
+/// How to convert speech to register writes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Settings {
+    /// The clock of the PSG in Hz.
+    pub clock: f64,
+
+    /// The number of times per second the player writes the registers, usually the 50 or 60 Hz
+    /// of the vertical blank interrupt. Rates are clamped to the range from 1 up to
+    /// [`super::MAX_FRAME_RATE`].
+    pub frame_rate: u32
+}
+
+impl Default for Settings {
+    /// An MSX with a 50 Hz display.
+    fn default() -> Self {
+        Self {
+            clock: MSX_CLOCK / 2.0,
+            frame_rate: 50
+        }
+    }
+}
+
+/// Convert phonemes to the PSG registers for each interrupt of a player.
+///
+/// The formants F1 to F3 play as the tones of channels A to C, with their amplitudes mapped to
+/// the closest volume. The sampled consonants play as noise on channel C, replacing the tones for
+/// unvoiced consonants. The PSG has no way to reproduce the glottal pulse, so the pitch of the
+/// voice is lost. The last registers silence all channels.
+///
+/// No envelope is used, so a player can skip R13 to avoid restarting the envelope.
+pub fn registers(phonemes: &[Phoneme], voice: &Voice, settings: &Settings) -> Vec<Registers> {
+    let frames = frames(phonemes, voice);
+
+    let mut dump: Vec<Registers> = interrupts(&frames, voice.speed(), settings.frame_rate)
+        .map(|frames| interrupt_registers(frames, settings.clock))
+        .collect();
+
+    if !dump.is_empty() {
+        dump.push(silence());
+    }
+
+    dump
+}
+
+/// Write the registers as a raw dump of 14 bytes for each interrupt.
+pub fn write_registers<W: Write>(mut writer: W, dump: &[Registers]) -> io::Result<()> {
+    for registers in dump {
+        writer.write_all(registers)?;
+    }
+
+    Ok(())
+}
+
+/// The closest volume to a linear amplitude from 0 to 15.
+pub fn volume(amplitude: u8) -> u8 {
+    let level = (amplitude & 0x0F) as f64 / 15.0;
+
+    (0..16)
+        .min_by(|a, b| (LEVELS[*a] - level).abs().total_cmp(&(LEVELS[*b] - level).abs()))
+        .unwrap_or(0) as u8
+}
+
+/// The tone period for a formant frequency value at the clock, limited to the 12 bits of the
+/// tone registers.
+pub fn tone_period(formant: u8, clock: f64) -> u16 {
+    (clock / (16.0 * renderer::formant_frequency(formant))).round().clamp(1.0, 4095.0) as u16
+}
+
+fn silence() -> Registers {
+    let mut registers = [0; REGISTER_COUNT];
+    registers[7] = MIXER_PORTS | 0b0011_1111;
+
+    registers
+}
+
+fn interrupt_registers(frames: &[Frame], clock: f64) -> Registers {
+    let mut registers = silence();
+
+    let flags = frames.iter()
+        .map(|frame| frame.sampled_consonant_flag)
+        .find(|flags| *flags != 0)
+        .unwrap_or(0);
+
+    let unvoiced = flags & 248 != 0;
+
+    if !unvoiced {
+        let formants = [
+            (mean(frames, |frame| frame.f1), mean(frames, |frame| frame.a1)),
+            (mean(frames, |frame| frame.f2), mean(frames, |frame| frame.a2)),
+            (mean(frames, |frame| frame.f3), mean(frames, |frame| frame.a3))
+        ];
+
+        for (channel, (formant, amplitude)) in formants.into_iter().enumerate() {
+            let volume = volume(amplitude);
+
+            if formant == 0 || volume == 0 {
+                continue;
+            }
+
+            let period = tone_period(formant, clock);
+
+            registers[channel * 2] = period as u8;
+            registers[channel * 2 + 1] = (period >> 8) as u8;
+            registers[7] &= !(1 << channel);
+            registers[8 + channel] = volume;
+        }
+    }
+
+    // The low bits select the sample of the consonant, starting at 1
+    if let Some(period) = (flags & 7).checked_sub(1).and_then(|kind| NOISE_PERIODS.get(kind as usize)) {
+        registers[6] = *period;
+        registers[7] &= !(1 << 5);
+        registers[10] = registers[10].max(if unvoiced { UNVOICED_VOLUME } else { VOICED_VOLUME });
+    }
+
+    registers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn volumes() {
+        assert_eq!(volume(0), 0);
+        assert_eq!(volume(15), 15);
+        assert_eq!(volume(8), 12);
+
+        for amplitude in 1..16 {
+            assert!(volume(amplitude) >= volume(amplitude - 1));
+        }
+
+        // 16 times the clock divided by the period gives the frequency
+        let clock = Settings::default().clock;
+        let period = tone_period(16, clock);
+        let frequency = clock / (16.0 * period as f64);
+        assert!((frequency - renderer::formant_frequency(16)).abs() < 1.0, "{}", frequency);
+    }
+
+    #[test]
+    fn registers_from_phonemes() {
+        let phonemes = crate::parser::parse_phonemes("/HEHLOW5 SAEM.").unwrap();
+        let voice = Voice::default();
+
+        assert!(registers(&[], &voice, &Settings::default()).is_empty());
+
+        let pal = registers(&phonemes, &voice, &Settings::default());
+        let ntsc = registers(&phonemes, &voice, &Settings { frame_rate: 60, ..Settings::default() });
+
+        // The length follows the frame rate
+        let seconds = frames(&phonemes, &voice).len() as f64 / renderer::frame_rate(voice.speed());
+        assert_eq!(pal.len(), (seconds * 50.0).ceil() as usize + 1);
+        assert_eq!(ntsc.len(), (seconds * 60.0).ceil() as usize + 1);
+
+        // Rates outside of what a player can do are clamped
+        let rate = |frame_rate| registers(&phonemes, &voice, &Settings { frame_rate, ..Settings::default() }).len();
+        assert_eq!(rate(0), rate(1));
+        assert_eq!(rate(u32::MAX), rate(crate::export::MAX_FRAME_RATE));
+
+        for registers in &pal {
+            assert_eq!(registers[7] & 0b1100_0000, MIXER_PORTS);
+            assert!(registers[8..11].iter().all(|volume| *volume < 16));
+        }
+
+        // The S plays as noise without tones
+        assert!(pal.iter().any(|registers| registers[7] & 0b0010_0111 == 0b0000_0111 && registers[6] == NOISE_PERIODS[0]));
+        assert!(pal.iter().any(|registers| registers[8] > 0 && registers[7] & 1 == 0));
+        assert_eq!(pal.last(), Some(&silence()));
+
+        let mut dump = Vec::new();
+        write_registers(&mut dump, &pal).unwrap();
+        assert_eq!(dump.len(), pal.len() * REGISTER_COUNT);
+    }
+}
@@ -0,0 +1,399 @@
+use std::io::{self, Write};
+
+use super::{frames, interrupts, mean, MSX_CLOCK};
+use crate::parser::Phoneme;
+use crate::renderer::{self, Frame, Voice};
+
+/// The number of channels of the SCC.
+pub const CHANNELS: usize = 5;
+
+/// The number of samples in a waveform.
+pub const WAVEFORM_LENGTH: usize = 32;
+
+/// The largest palette a raw dump can hold, as it stores waveform numbers in a byte.
+pub const MAX_PALETTE_SIZE: usize = 255;
+
+/// A waveform of signed samples, which a channel plays once per period.
+pub type Waveform = [i8; WAVEFORM_LENGTH];
+
+// The palette always starts with the waveforms of the formant oscillators
+const SINE: usize = 0;
+const SQUARE: usize = 1;
+const FORMANT_WAVEFORMS: usize = 2;
+
+// Channels 4 and 5 share a waveform on the original SCC, so only channel 4 is used
+const CONSONANT_CHANNEL: usize = 3;
+
+// The renderer plays a bit of a sampled consonant about every 60 / 50 samples
+const CONSONANT_BIT_RATE: f64 = renderer::SAMPLE_RATE as f64 * 50.0 / 60.0;
+
+// The volume of unvoiced consonants, and of the consonant channel for voiced ones
+const UNVOICED_VOLUME: u8 = 12;
+const VOICED_VOLUME: u8 = 9;
+
+// The number of times the palette is refined
+const ITERATIONS: usize = 16;
+
+/// How to convert speech to SCC data.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Settings {
+    /// The clock of the SCC in Hz.
+    pub clock: f64,
+
+    /// The number of times per second the player writes the registers, usually the 50 or 60 Hz
+    /// of the vertical blank interrupt. Rates are clamped to the range from 1 up to
+    /// [`super::MAX_FRAME_RATE`].
+    pub frame_rate: u32,
+
+    /// The maximum number of distinct waveforms, from 3 up to [`MAX_PALETTE_SIZE`]. Other sizes
+    /// are clamped to that range.
+    pub palette_size: usize,
+
+    /// The number of interrupts channel 4 holds a consonant waveform before it may switch to
+    /// another one. Every switch uploads 32 bytes of waveform, so this limits the uploads to one
+    /// every so many interrupts. A hold of 0 or 1 switches whenever the consonant does.
+    pub waveform_hold: u32
+}
+
+impl Default for Settings {
+    /// An MSX with a 50 Hz display, a palette of 8 waveforms and a new consonant waveform at
+    /// most every other interrupt.
+    fn default() -> Self {
+        Self {
+            clock: MSX_CLOCK,
+            frame_rate: 50,
+            palette_size: 8,
+            waveform_hold: 2
+        }
+    }
+}
+
+/// The registers of a channel during an interrupt.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Channel {
+    /// The position of the waveform in the palette.
+    pub waveform: usize,
+
+    /// The 12 bit frequency register. The channel plays the clock divided by 32 times the
+    /// period plus one.
+    pub period: u16,
+
+    /// The 4 bit volume, 0 when the channel is disabled.
+    pub volume: u8
+}
+
+/// SCC data for speech: a palette of waveforms and the channel registers for each interrupt.
+///
+/// Channels 1 to 3 play the formants F1 to F3 with the waveforms of their oscillators, a sine for
+/// F1 and F2 and a square for F3. The SCC has no noise, so channel 4 plays the bits of the sampled
+/// consonants as waveforms. The SCC holds a single waveform for each channel, so the player
+/// uploads the waveform of channel 4 whenever it changes. The consonant waveforms are reduced to a
+/// palette of the most representative ones, which keeps the stored data small, and each one is
+/// held for [`Settings::waveform_hold`] interrupts, which limits how often it is uploaded.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Dump {
+    pub palette: Vec<Waveform>,
+    pub frames: Vec<[Channel; CHANNELS]>
+}
+
+impl Dump {
+    /// The channel enable register for the channels of an interrupt.
+    pub fn enabled(channels: &[Channel; CHANNELS]) -> u8 {
+        channels.iter()
+            .enumerate()
+            .filter(|(_, channel)| channel.volume > 0)
+            .fold(0, |enabled, (index, _)| enabled | 1 << index)
+    }
+
+    /// Write the dump in a raw format for a player: the number of waveforms as a byte and the
+    /// waveforms, followed by 21 bytes for each interrupt. These are the waveform, the period as
+    /// a little endian word and the volume of each channel, and the channel enable register.
+    pub fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_all(&[self.palette.len() as u8])?;
+
+        for waveform in &self.palette {
+            writer.write_all(&waveform.map(|sample| sample as u8))?;
+        }
+
+        for channels in &self.frames {
+            for channel in channels {
+                writer.write_all(&[channel.waveform as u8])?;
+                writer.write_all(&channel.period.to_le_bytes())?;
+                writer.write_all(&[channel.volume])?;
+            }
+
+            writer.write_all(&[Self::enabled(channels)])?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Convert phonemes to SCC data for each interrupt of a player. The last interrupt disables all
+/// channels.
+pub fn export(phonemes: &[Phoneme], voice: &Voice, settings: &Settings) -> Dump {
+    let frames = frames(phonemes, voice);
+
+    let mut consonants = Vec::new();
+    let mut dump = Dump::default();
+
+    let mut previous_flags = 0;
+    let mut offset = 0_u8;
+
+    for frames in interrupts(&frames, voice.speed(), settings.frame_rate) {
+        let flags = frames.iter()
+            .map(|frame| frame.sampled_consonant_flag)
+            .find(|flags| *flags != 0)
+            .unwrap_or(0);
+
+        let mut channels = formant_channels(frames, flags, settings.clock);
+
+        // The low bits select the sample of the consonant, starting at 1
+        if let Some(kind) = (flags & 7).checked_sub(1).filter(|kind| *kind < 5) {
+            // Start where the renderer starts a consonant, and continue with the next bits
+            if flags != previous_flags {
+                offset = (flags & 248) ^ 255;
+            }
+
+            channels[CONSONANT_CHANNEL] = Channel {
+                waveform: FORMANT_WAVEFORMS + consonants.len(),
+                period: period(CONSONANT_BIT_RATE / WAVEFORM_LENGTH as f64, settings.clock),
+                volume: if flags & 248 != 0 { UNVOICED_VOLUME } else { VOICED_VOLUME }
+            };
+
+            consonants.push(consonant_waveform(kind, offset));
+            offset = offset.wrapping_add((WAVEFORM_LENGTH / 8) as u8);
+        }
+
+        previous_flags = flags;
+        dump.frames.push(channels);
+    }
+
+    if !dump.frames.is_empty() {
+        dump.frames.push([Channel::default(); CHANNELS]);
+    }
+
+    let (centroids, assignments) = cluster(&consonants, settings.palette_size.clamp(FORMANT_WAVEFORMS + 1, MAX_PALETTE_SIZE) - FORMANT_WAVEFORMS);
+
+    for channel in dump.frames.iter_mut().map(|channels| &mut channels[CONSONANT_CHANNEL]).filter(|channel| channel.volume > 0) {
+        channel.waveform = FORMANT_WAVEFORMS + assignments[channel.waveform - FORMANT_WAVEFORMS];
+    }
+
+    // Keep playing the current waveform until it has been held long enough
+    let mut held: Option<(usize, u32)> = None;
+
+    for channel in dump.frames.iter_mut().map(|channels| &mut channels[CONSONANT_CHANNEL]).filter(|channel| channel.volume > 0) {
+        held = match held {
+            Some((waveform, count)) if count < settings.waveform_hold => {
+                channel.waveform = waveform;
+                Some((waveform, count + 1))
+            },
+            Some((waveform, count)) if waveform == channel.waveform => Some((waveform, count + 1)),
+            _ => Some((channel.waveform, 1))
+        };
+    }
+
+    dump.palette = [sine(), square()].into_iter().chain(centroids).collect();
+    dump
+}
+
+/// The frequency register for a frequency in Hz at the clock, limited to 12 bits.
+pub fn period(frequency: f64, clock: f64) -> u16 {
+    (clock / (WAVEFORM_LENGTH as f64 * frequency) - 1.0).round().clamp(0.0, 4095.0) as u16
+}
+
+fn sine() -> Waveform {
+    std::array::from_fn(|index| renderer::sinus((index * 256 / WAVEFORM_LENGTH) as u8))
+}
+
+// The square oscillator of F3 is low for the first half of its phase
+fn square() -> Waveform {
+    std::array::from_fn(|index| if index * 256 / WAVEFORM_LENGTH < 129 { -0x70 } else { 0x70 })
+}
+
+fn formant_channels(frames: &[Frame], flags: u8, clock: f64) -> [Channel; CHANNELS] {
+    let mut channels = [Channel::default(); CHANNELS];
+
+    // Unvoiced consonants replace the formants
+    if flags & 248 != 0 {
+        return channels;
+    }
+
+    let formants = [
+        (mean(frames, |frame| frame.f1), mean(frames, |frame| frame.a1), SINE),
+        (mean(frames, |frame| frame.f2), mean(frames, |frame| frame.a2), SINE),
+        (mean(frames, |frame| frame.f3), mean(frames, |frame| frame.a3), SQUARE)
+    ];
+
+    for (channel, (formant, amplitude, waveform)) in channels.iter_mut().zip(formants) {
+        // The SCC volume is linear like the amplitudes of the renderer
+        let volume = amplitude & 0x0F;
+
+        if formant == 0 || volume == 0 {
+            continue;
+        }
+
+        *channel = Channel {
+            waveform,
+            period: period(renderer::formant_frequency(formant), clock),
+            volume
+        };
+    }
+
+    channels
+}
+
+// The next 32 bits of a sampled consonant as a waveform
+fn consonant_waveform(kind: u8, offset: u8) -> Waveform {
+    let page = kind as usize * 256;
+
+    std::array::from_fn(|index| {
+        let byte = renderer::SAMPLE_TABLE[page + offset.wrapping_add((index / 8) as u8) as usize];
+
+        match byte & (0x80 >> (index % 8)) {
+            0 => 0x70,
+            _ => -0x70
+        }
+    })
+}
+
+// The squared distance between two waveforms
+fn distance(a: &Waveform, b: &Waveform) -> u32 {
+    a.iter().zip(b).map(|(a, b)| (*a as i32 - *b as i32).pow(2) as u32).sum()
+}
+
+fn nearest(centroids: &[Waveform], waveform: &Waveform) -> usize {
+    (0..centroids.len())
+        .min_by_key(|index| distance(&centroids[*index], waveform))
+        .unwrap_or(0)
+}
+
+// Reduce the waveforms to at most the given number using k-means clustering, returning the
+// centroids and the centroid of each waveform
+fn cluster(waveforms: &[Waveform], count: usize) -> (Vec<Waveform>, Vec<usize>) {
+    let mut centroids = waveforms.to_vec();
+    centroids.sort();
+    centroids.dedup();
+
+    if centroids.len() > count {
+        // Start from the first waveform and repeatedly add the one farthest from the centroids
+        centroids = vec![waveforms[0]];
+
+        while centroids.len() < count {
+            let farthest = waveforms.iter()
+                .max_by_key(|waveform| distance(&centroids[nearest(&centroids, waveform)], waveform))
+                .copied()
+                .unwrap_or_default();
+
+            centroids.push(farthest);
+        }
+
+        for _ in 0..ITERATIONS {
+            let mut sums = vec![([0_i32; WAVEFORM_LENGTH], 0_i32); count];
+
+            for waveform in waveforms {
+                let (sum, members) = &mut sums[nearest(&centroids, waveform)];
+
+                for (sum, sample) in sum.iter_mut().zip(waveform) {
+                    *sum += *sample as i32;
+                }
+
+                *members += 1;
+            }
+
+            // A centroid without members stays in place
+            let updated: Vec<Waveform> = sums.iter().zip(&centroids).map(|((sum, members), centroid)| match members {
+                0 => *centroid,
+                _ => sum.map(|sum| (sum as f64 / *members as f64).round() as i8)
+            }).collect();
+
+            if updated == centroids {
+                break;
+            }
+
+            centroids = updated;
+        }
+    }
+
+    let assignments = waveforms.iter().map(|waveform| nearest(&centroids, waveform)).collect();
+
+    (centroids, assignments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn waveforms() {
+        let sine = sine();
+        assert_eq!(sine[0], 0);
+        assert_eq!(sine[8], 127);
+        assert_eq!(sine[24], -127);
+        assert_eq!(square()[..17].iter().filter(|sample| **sample < 0).count(), 17);
+
+        // The clock divided by 32 times the period plus one gives the frequency
+        let period = period(440.0, MSX_CLOCK);
+        assert!((MSX_CLOCK / (32.0 * (period as f64 + 1.0)) - 440.0).abs() < 2.0);
+    }
+
+    #[test]
+    fn palette() {
+        let waveforms = [[10; WAVEFORM_LENGTH], [12; WAVEFORM_LENGTH], [-50; WAVEFORM_LENGTH], [-54; WAVEFORM_LENGTH]];
+
+        let (centroids, assignments) = cluster(&waveforms, 2);
+        assert_eq!(centroids.len(), 2);
+        assert_eq!(assignments[0], assignments[1]);
+        assert_eq!(assignments[2], assignments[3]);
+        assert_eq!(centroids[assignments[0]], [11; WAVEFORM_LENGTH]);
+
+        let (centroids, assignments) = cluster(&waveforms[..2], 4);
+        assert_eq!(centroids.len(), 2);
+        assert_eq!(centroids[assignments[1]], waveforms[1]);
+    }
+
+    #[test]
+    fn export_phonemes() {
+        let phonemes = crate::parser::parse_phonemes("/HEHLOW5 SAEM.").unwrap();
+        let voice = Voice::default();
+        let settings = Settings {
+            palette_size: 4,
+            ..Settings::default()
+        };
+
+        let dump = export(&phonemes, &voice, &settings);
+
+        assert_eq!(dump.palette.len(), 4);
+        assert_eq!(dump.frames.len(), crate::export::psg::registers(&phonemes, &voice, &crate::export::psg::Settings::default()).len());
+        assert_eq!(dump.frames.last(), Some(&[Channel::default(); CHANNELS]));
+
+        for channels in &dump.frames {
+            assert!(channels.iter().all(|channel| channel.waveform < dump.palette.len() && channel.period < 4096 && channel.volume < 16));
+            assert_eq!(channels[4], Channel::default());
+        }
+
+        // The S replaces the formants with a consonant waveform
+        assert!(dump.frames.iter().any(|channels| Dump::enabled(channels) == 0b1000 && channels[3].waveform >= FORMANT_WAVEFORMS));
+        assert!(dump.frames.iter().any(|channels| channels[0].volume > 0 && channels[2].waveform == SQUARE));
+
+        let mut bytes = Vec::new();
+        dump.write(&mut bytes).unwrap();
+        assert_eq!(bytes.len(), 1 + 4 * WAVEFORM_LENGTH + dump.frames.len() * 21);
+
+        // A held consonant waveform plays for at least that many interrupts before it changes
+        let switches = |hold: u32| {
+            let dump = export(&phonemes, &voice, &Settings { waveform_hold: hold, ..settings });
+            let waveforms: Vec<usize> = dump.frames.iter().map(|channels| channels[3]).filter(|channel| channel.volume > 0).map(|channel| channel.waveform).collect();
+            let runs: Vec<usize> = waveforms.chunk_by(|a, b| a == b).map(|run| run.len()).collect();
+
+            assert!(runs[..runs.len() - 1].iter().all(|run| *run >= hold as usize), "{} {:?}", hold, runs);
+            runs.len()
+        };
+
+        assert!(switches(4) < switches(1));
+
+        // The palette never outgrows the byte that stores its size
+        let dump = export(&phonemes, &voice, &Settings { palette_size: usize::MAX, ..Settings::default() });
+        assert!(dump.palette.len() <= MAX_PALETTE_SIZE);
+    }
+}
@@ -0,0 +1,275 @@
+use std::io::{self, Write};
+
+use super::{clamp_frame_rate, psg, scc};
+
+/// The sample rate that VGM wait commands count in.
+pub const SAMPLE_RATE: u32 = 44100;
+
+const VERSION: u32 = 0x171;
+const HEADER_SIZE: usize = 0x100;
+
+// Header fields, as offsets into the header
+const EOF_OFFSET: usize = 0x04;
+const VERSION_NUMBER: usize = 0x08;
+const TOTAL_SAMPLES: usize = 0x18;
+const LOOP_OFFSET: usize = 0x1C;
+const LOOP_SAMPLES: usize = 0x20;
+const DATA_OFFSET: usize = 0x34;
+const AY8910_CLOCK: usize = 0x74;
+const AY8910_TYPE: usize = 0x78;
+const AY8910_FLAGS: usize = 0x79;
+const K051649_CLOCK: usize = 0x9C;
+
+// Commands
+const AY8910_WRITE: u8 = 0xA0;
+const K051649_WRITE: u8 = 0xD2;
+const WAIT: u8 = 0x61;
+const WAIT_NTSC_FRAME: u8 = 0x62;
+const WAIT_PAL_FRAME: u8 = 0x63;
+const WAIT_SHORT: u8 = 0x70;
+const END: u8 = 0x66;
+
+// The ports of the K051649
+const WAVEFORM_PORT: u8 = 0x00;
+const FREQUENCY_PORT: u8 = 0x01;
+const VOLUME_PORT: u8 = 0x02;
+const ENABLE_PORT: u8 = 0x03;
+
+// The PSG registers up to the volumes, the envelope is not used
+const PSG_REGISTERS: usize = 11;
+
+// The channels with their own waveform on the SCC
+const SCC_WAVEFORMS: usize = 4;
+
+/// Write PSG registers as a VGM file for an AY-3-8910, with the registers that changed at each
+/// interrupt. When looping the whole file repeats.
+pub fn write_psg<W: Write>(writer: W, dump: &[psg::Registers], settings: &psg::Settings, looping: bool) -> io::Result<()> {
+    let mut vgm = Vgm::new(settings.frame_rate);
+    let mut previous: Option<&psg::Registers> = None;
+
+    for registers in dump {
+        for register in 0..PSG_REGISTERS {
+            if previous.is_none_or(|previous| previous[register] != registers[register]) {
+                vgm.data.extend([AY8910_WRITE, register as u8, registers[register]]);
+            }
+        }
+
+        previous = Some(registers);
+        vgm.next_interrupt();
+    }
+
+    let mut header = vgm.header(looping);
+    header[AY8910_CLOCK..AY8910_CLOCK + 4].copy_from_slice(&(settings.clock as u32).to_le_bytes());
+    header[AY8910_TYPE] = 0x00; // AY8910
+    header[AY8910_FLAGS] = 0x01; // Legacy output
+
+    vgm.write(writer, &header)
+}
+
+/// Write SCC data as a VGM file for a K051649, with the registers that changed at each
+/// interrupt. The waveform of a channel is written when it changes. When looping the whole file
+/// repeats.
+pub fn write_scc<W: Write>(writer: W, dump: &scc::Dump, settings: &scc::Settings, looping: bool) -> io::Result<()> {
+    let mut vgm = Vgm::new(settings.frame_rate);
+    let mut previous: Option<&[scc::Channel; scc::CHANNELS]> = None;
+
+    for channels in &dump.frames {
+        for (index, channel) in channels.iter().enumerate() {
+            let old = previous.map(|previous| previous[index]);
+            let changed = |value: fn(&scc::Channel) -> usize| old.is_none_or(|old| value(&old) != value(channel));
+
+            if index < SCC_WAVEFORMS && changed(|channel| channel.waveform) {
+                for (position, sample) in dump.palette[channel.waveform].iter().enumerate() {
+                    vgm.data.extend([K051649_WRITE, WAVEFORM_PORT, (index * scc::WAVEFORM_LENGTH + position) as u8, *sample as u8]);
+                }
+            }
+
+            if changed(|channel| channel.period as usize) {
+                let [low, high] = channel.period.to_le_bytes();
+                vgm.data.extend([K051649_WRITE, FREQUENCY_PORT, index as u8 * 2, low]);
+                vgm.data.extend([K051649_WRITE, FREQUENCY_PORT, index as u8 * 2 + 1, high]);
+            }
+
+            if changed(|channel| channel.volume as usize) {
+                vgm.data.extend([K051649_WRITE, VOLUME_PORT, index as u8, channel.volume]);
+            }
+        }
+
+        let enabled = scc::Dump::enabled(channels);
+        if previous.is_none_or(|previous| scc::Dump::enabled(previous) != enabled) {
+            vgm.data.extend([K051649_WRITE, ENABLE_PORT, 0x00, enabled]);
+        }
+
+        previous = Some(channels);
+        vgm.next_interrupt();
+    }
+
+    // VGM files clock the K051649 at half the clock of the MSX
+    let mut header = vgm.header(looping);
+    header[K051649_CLOCK..K051649_CLOCK + 4].copy_from_slice(&((settings.clock / 2.0) as u32).to_le_bytes());
+
+    vgm.write(writer, &header)
+}
+
+// The commands of a VGM file and the number of samples they last
+struct Vgm {
+    data: Vec<u8>,
+    frame_rate: u32,
+    interrupts: u64,
+    samples: u64
+}
+
+impl Vgm {
+    fn new(frame_rate: u32) -> Self {
+        Self {
+            data: Vec::new(),
+            frame_rate: clamp_frame_rate(frame_rate),
+            interrupts: 0,
+            samples: 0
+        }
+    }
+
+    // Wait until the next interrupt, rounding its start to the nearest sample
+    fn next_interrupt(&mut self) {
+        self.interrupts += 1;
+
+        let end = (self.interrupts * SAMPLE_RATE as u64 * 2 + self.frame_rate as u64) / (self.frame_rate as u64 * 2);
+        self.wait((end - self.samples) as u32);
+        self.samples = end;
+    }
+
+    fn wait(&mut self, mut samples: u32) {
+        while samples > 0 {
+            let length = match samples {
+                882 => {
+                    self.data.push(WAIT_PAL_FRAME);
+                    882
+                },
+                735 => {
+                    self.data.push(WAIT_NTSC_FRAME);
+                    735
+                },
+                1..=16 => {
+                    self.data.push(WAIT_SHORT + samples as u8 - 1);
+                    samples
+                },
+                _ => {
+                    let length = samples.min(u16::MAX as u32);
+                    self.data.push(WAIT);
+                    self.data.extend((length as u16).to_le_bytes());
+                    length
+                }
+            };
+
+            samples -= length;
+        }
+    }
+
+    // The header without any chips. Offsets in the header are relative to their own position.
+    fn header(&self, looping: bool) -> [u8; HEADER_SIZE] {
+        let mut header = [0; HEADER_SIZE];
+
+        let mut field = |offset: usize, value: u32| {
+            header[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+        };
+
+        field(EOF_OFFSET, (HEADER_SIZE + self.data.len() + 1 - EOF_OFFSET) as u32);
+        field(VERSION_NUMBER, VERSION);
+        field(TOTAL_SAMPLES, self.samples as u32);
+        field(DATA_OFFSET, (HEADER_SIZE - DATA_OFFSET) as u32);
+
+        if looping {
+            field(LOOP_OFFSET, (HEADER_SIZE - LOOP_OFFSET) as u32);
+            field(LOOP_SAMPLES, self.samples as u32);
+        }
+
+        header[..4].copy_from_slice(b"Vgm ");
+        header
+    }
+
+    fn write<W: Write>(&self, mut writer: W, header: &[u8; HEADER_SIZE]) -> io::Result<()> {
+        writer.write_all(header)?;
+        writer.write_all(&self.data)?;
+        writer.write_all(&[END])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(vgm: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(vgm[offset..offset + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn waits() {
+        let wait = |samples| {
+            let mut vgm = Vgm::new(50);
+            vgm.wait(samples);
+            vgm.data
+        };
+
+        assert_eq!(wait(882), [WAIT_PAL_FRAME]);
+        assert_eq!(wait(735), [WAIT_NTSC_FRAME]);
+        assert_eq!(wait(1), [0x70]);
+        assert_eq!(wait(16), [0x7F]);
+        assert_eq!(wait(1000), [WAIT, 0xE8, 0x03]);
+        assert_eq!(wait(65536), [WAIT, 0xFF, 0xFF, 0x70]);
+
+        // Interrupts that do not divide the sample rate alternate between lengths
+        let mut vgm = Vgm::new(70);
+        (0..70).for_each(|_| vgm.next_interrupt());
+        assert_eq!(vgm.samples, SAMPLE_RATE as u64);
+    }
+
+    #[test]
+    fn psg_file() {
+        let phonemes = crate::parser::parse_phonemes("/HEHLOW5.").unwrap();
+        let voice = crate::renderer::Voice::default();
+        let settings = psg::Settings::default();
+        let dump = psg::registers(&phonemes, &voice, &settings);
+
+        let mut vgm = Vec::new();
+        write_psg(&mut vgm, &dump, &settings, true).unwrap();
+
+        assert_eq!(&vgm[..4], b"Vgm ");
+        assert_eq!(field(&vgm, EOF_OFFSET) as usize, vgm.len() - EOF_OFFSET);
+        assert_eq!(field(&vgm, VERSION_NUMBER), 0x171);
+        assert_eq!(field(&vgm, TOTAL_SAMPLES), dump.len() as u32 * 882);
+        assert_eq!(field(&vgm, LOOP_OFFSET) as usize + LOOP_OFFSET, HEADER_SIZE);
+        assert_eq!(field(&vgm, LOOP_SAMPLES), field(&vgm, TOTAL_SAMPLES));
+        assert_eq!(field(&vgm, DATA_OFFSET) as usize + DATA_OFFSET, HEADER_SIZE);
+        assert_eq!(field(&vgm, AY8910_CLOCK), 1789772);
+        assert_eq!(field(&vgm, K051649_CLOCK), 0);
+
+        // All registers are written at the start, and each interrupt ends with a wait
+        assert_eq!(&vgm[HEADER_SIZE..HEADER_SIZE + 3], [AY8910_WRITE, 0, dump[0][0]]);
+        assert_eq!(vgm.iter().filter(|byte| **byte == WAIT_PAL_FRAME).count(), dump.len());
+        assert_eq!(vgm.last(), Some(&END));
+    }
+
+    #[test]
+    fn scc_file() {
+        let phonemes = crate::parser::parse_phonemes("SAEM.").unwrap();
+        let voice = crate::renderer::Voice::default();
+        let settings = scc::Settings {
+            frame_rate: 60,
+            ..scc::Settings::default()
+        };
+        let dump = scc::export(&phonemes, &voice, &settings);
+
+        let mut vgm = Vec::new();
+        write_scc(&mut vgm, &dump, &settings, false).unwrap();
+
+        assert_eq!(field(&vgm, K051649_CLOCK), 1789772);
+        assert_eq!(field(&vgm, AY8910_CLOCK), 0);
+        assert_eq!(field(&vgm, LOOP_OFFSET), 0);
+        assert_eq!(field(&vgm, TOTAL_SAMPLES), dump.frames.len() as u32 * 735);
+
+        // The data starts by uploading the waveform of the first channel
+        let first = &vgm[HEADER_SIZE..HEADER_SIZE + 4 * scc::WAVEFORM_LENGTH];
+        assert!(first.chunks(4).enumerate().all(|(position, command)| command[..3] == [K051649_WRITE, WAVEFORM_PORT, position as u8]));
+        assert_eq!(vgm.last(), Some(&END));
+    }
+}
@@ -1,3 +1,24 @@
+pub mod export;
 pub mod parser;
 pub mod reciter;
 pub mod renderer;
+pub mod viseme;
+pub mod wav;
+
+mod sam;
+
+pub use sam::{Error, Sam, speak};
+
+/// Selects between reproducing the bugs of the SamJs reference implementation and fixing them.
+///
+/// Each stage of the pipeline documents the bugs it corrects. The default is [`Compatibility::SamJs`],
+/// which produces output that is identical to the reference.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Compatibility {
+    /// Reproduce the reference implementation bug-for-bug
+    #[default]
+    SamJs,
+
+    /// Fix the known bugs of the reference implementation
+    Corrected
+}
@@ -0,0 +1,499 @@
+use std::io::{Read, Write};
+use std::path::PathBuf;
+
+use clap::{Parser, ValueEnum};
+
+use rustsam::{renderer, wav, Compatibility, Sam};
+use rustsam::export::{self, emit, pcm, psg, scc, vgm};
+use rustsam::reciter::{Dictionary, DictionaryError, Reciter, RuleSet, RuleSetError};
+use rustsam::renderer::{Preset, Voice, VoiceError};
+
+#[derive(Debug)]
+enum Error {
+    Message(&'static str),
+    IO(&'static str, std::io::Error),
+    Sam(rustsam::Error),
+    Voice(VoiceError),
+    Dictionary(DictionaryError),
+    Rules(RuleSetError)
+}
+
+impl std::error::Error for Error {}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::Message(message) => write!(f, "{}", message),
+            Error::IO(message, err) => write!(f, "{} ({})", message, err),
+            Error::Sam(err) => write!(f, "{}", err),
+            Error::Voice(err) => write!(f, "{}", err),
+            Error::Dictionary(err) => write!(f, "{}", err),
+            Error::Rules(err) => write!(f, "{}", err)
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum SampleFormat {
+    /// Unsigned 8 bit, as rendered by SAM
+    U8,
+
+    /// Signed 16 bit
+    S16,
+
+    /// 32 bit floating point
+    F32
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Curve {
+    /// Evenly spaced levels, as on the C64
+    Linear,
+
+    /// The logarithmic volume levels of the AY-3-8910
+    Ay
+}
+
+impl From<Curve> for pcm::Curve {
+    fn from(curve: Curve) -> Self {
+        match curve {
+            Curve::Linear => pcm::Curve::Linear,
+            Curve::Ay => pcm::Curve::Ay
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Chip {
+    /// The AY-3-8910 PSG
+    Psg,
+
+    /// The Konami SCC
+    Scc
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Emit {
+    /// The bytes as they are
+    Raw,
+
+    /// A binary file with a header giving the length, rate and format
+    Binary,
+
+    /// A db include file for sjasmplus or tniASM
+    Db,
+
+    /// MSX-BASIC DATA lines
+    Basic
+}
+
+impl From<SampleFormat> for wav::Format {
+    fn from(format: SampleFormat) -> Self {
+        match format {
+            SampleFormat::U8 => wav::Format::Unsigned8,
+            SampleFormat::S16 => wav::Format::Signed16,
+            SampleFormat::F32 => wav::Format::Float32
+        }
+    }
+}
+
+/// Software Automatic Mouth speech synthesizer
+#[derive(Parser)]
+#[command(version)]
+struct Cli {
+    /// The text to speak, read from stdin when omitted
+    text: Vec<String>,
+
+    /// Treat the input as SAM phonemes instead of English text
+    #[arg(short='P', long)]
+    phonetic: bool,
+
+    /// Print the phonemes produced by the reciter instead of speaking
+    #[arg(long)]
+    phonemes_only: bool,
+
+    /// Print the rules the reciter tries for each part of the text instead of speaking
+    #[arg(long, conflicts_with="phonetic")]
+    explain: bool,
+
+    /// Pronunciation dictionary with a word and its phonemes on each line
+    #[arg(short='d', long, value_name="FILE")]
+    dictionary: Option<PathBuf>,
+
+    /// Read numbers, ordinals, amounts of money, years and times as words
+    #[arg(short='N', long, conflicts_with="phonetic")]
+    numbers: bool,
+
+    /// Reciter rule set with a "prefix(source)suffix -> phonemes" rule on each line
+    #[arg(long, value_name="FILE")]
+    rules: Option<PathBuf>,
+
+    /// Voice preset to start from (see --list-voices)
+    #[arg(short='v', long, default_value="SAM")]
+    voice: String,
+
+    /// List the available voice presets
+    #[arg(long)]
+    list_voices: bool,
+
+    /// Pitch (1-255, lower is higher pitched)
+    #[arg(short, long)]
+    pitch: Option<u8>,
+
+    /// Speed (1-255, lower is faster)
+    #[arg(short, long)]
+    speed: Option<u8>,
+
+    /// Mouth size (0-255)
+    #[arg(short, long)]
+    mouth: Option<u8>,
+
+    /// Throat size (0-255)
+    #[arg(short, long)]
+    throat: Option<u8>,
+
+    /// Enable sing mode
+    #[arg(long)]
+    sing: bool,
+
+    /// Output sample rate in Hz (4000-192000)
+    #[arg(long, default_value_t=renderer::SAMPLE_RATE)]
+    rate: u32,
+
+    /// Fix the bugs of the SamJs reference implementation instead of reproducing them
+    #[arg(long)]
+    corrected: bool,
+
+    /// Sample format of the WAV file or raw output
+    #[arg(short, long, value_enum, default_value="u8")]
+    format: SampleFormat,
+
+    /// Multiply the samples by this factor, clipping the result
+    #[arg(short, long)]
+    gain: Option<f32>,
+
+    /// Scale the output to full volume
+    #[arg(short, long, conflicts_with="gain")]
+    normalize: bool,
+
+    /// Write a WAV file
+    #[arg(short, long, value_name="FILE")]
+    output: Option<PathBuf>,
+
+    /// Write raw little endian samples to stdout
+    #[arg(short, long, conflicts_with="output")]
+    raw: bool,
+
+    /// Write a dump of the 14 AY-3-8910 registers for each interrupt instead of audio
+    #[arg(long, value_name="FILE", conflicts_with_all=["output", "raw"])]
+    psg: Option<PathBuf>,
+
+    /// Write SCC waveforms and channel registers for each interrupt instead of audio
+    #[arg(long, value_name="FILE", conflicts_with_all=["output", "raw", "psg"])]
+    scc: Option<PathBuf>,
+
+    /// Write a VGM file for the chip selected with --chip instead of audio
+    #[arg(long, value_name="FILE", conflicts_with_all=["output", "raw", "psg", "scc"])]
+    vgm: Option<PathBuf>,
+
+    /// Sound chip to write the VGM file for
+    #[arg(long, value_enum, default_value="psg")]
+    chip: Chip,
+
+    /// Make the VGM file loop
+    #[arg(long="loop")]
+    looping: bool,
+
+    /// Write packed 4 bit samples for playback through a volume register instead of audio
+    #[arg(long, value_name="FILE", conflicts_with_all=["output", "raw", "psg", "scc", "vgm"])]
+    pcm4: Option<PathBuf>,
+
+    /// Levels of the volume register for 4 bit samples
+    #[arg(long, value_enum, default_value="linear")]
+    curve: Curve,
+
+    /// Spread the quantization error of 4 bit samples over the following samples
+    #[arg(long)]
+    dither: bool,
+
+    /// Maximum number of distinct SCC waveforms
+    #[arg(long, default_value_t=8, value_parser=clap::value_parser!(u8).range(3..))]
+    palette_size: u8,
+
+    /// Minimum number of interrupts between SCC consonant waveform changes
+    #[arg(long, default_value_t=2)]
+    waveform_hold: u32,
+
+    /// Interrupt rate in Hz of the player for register dumps and VGM files
+    #[arg(long, default_value_t=50, value_parser=clap::value_parser!(u32).range(1..=export::MAX_FRAME_RATE as i64))]
+    frame_rate: u32,
+
+    /// How to write raw samples, register dumps and 4 bit samples
+    #[arg(long, value_enum, default_value="raw", conflicts_with_all=["output", "vgm"])]
+    emit: Emit,
+
+    /// Compress raw samples, register dumps and 4 bit samples with run length encoding
+    #[arg(long, conflicts_with_all=["output", "vgm"])]
+    rle: bool,
+
+    /// Label of the data in db include files
+    #[arg(long, default_value="speech")]
+    label: String,
+
+    /// First line number of MSX-BASIC DATA lines
+    #[arg(long, default_value_t=1000, value_parser=clap::value_parser!(u16).range(0..=65529))]
+    basic_line: u16,
+
+    /// Play the audio on the default sound card
+    #[cfg(feature = "playback")]
+    #[arg(long, conflicts_with_all=["output", "raw"])]
+    play: bool
+}
+
+impl Cli {
+    fn input(&self) -> Result<String, Error> {
+        if !self.text.is_empty() {
+            return Ok(self.text.join(" "));
+        }
+
+        let mut input = String::new();
+        std::io::stdin().read_to_string(&mut input)
+            .map_err(|err| Error::IO("Could not read from stdin", err))?;
+
+        Ok(input.trim_end().to_owned())
+    }
+
+    fn compatibility(&self) -> Compatibility {
+        if self.corrected {
+            Compatibility::Corrected
+        } else {
+            Compatibility::SamJs
+        }
+    }
+
+    fn voice(&self) -> Result<Voice, Error> {
+        let preset: Preset = self.voice.parse().map_err(Error::Voice)?;
+        let mut builder = Voice::preset(preset).to_builder();
+
+        if let Some(pitch) = self.pitch {
+            builder = builder.pitch(pitch);
+        }
+
+        if let Some(speed) = self.speed {
+            builder = builder.speed(speed);
+        }
+
+        if let Some(mouth) = self.mouth {
+            builder = builder.mouth(mouth);
+        }
+
+        if let Some(throat) = self.throat {
+            builder = builder.throat(throat);
+        }
+
+        if self.sing {
+            builder = builder.sing_mode(true);
+        }
+
+        builder
+            .compatibility(self.compatibility())
+            .sample_rate(self.rate)
+            .build().map_err(Error::Voice)
+    }
+}
+
+#[cfg(feature = "playback")]
+fn play(samples: &[f32], sample_rate: u32) -> Result<(), Error> {
+    use rodio::{OutputStream, Sink};
+    use rodio::buffer::SamplesBuffer;
+
+    let (_stream, stream_handle) = OutputStream::try_default().map_err(|_| Error::Message("Could not open audio device"))?;
+    let sink = Sink::try_new(&stream_handle).map_err(|_| Error::Message("Could not create audio sink"))?;
+
+    sink.append(SamplesBuffer::new(1, sample_rate, samples.iter().map(|sample| sample.clamp(-1.0, 1.0)).collect::<Vec<_>>()));
+    sink.sleep_until_end();
+
+    Ok(())
+}
+
+// Write the selected sound chip data instead of audio. Returns false if none was selected.
+fn export(cli: &Cli, voice: &Voice, phonetic: &str) -> Result<bool, Error> {
+    let Some(path) = cli.psg.as_ref().or(cli.scc.as_ref()).or(cli.vgm.as_ref()) else {
+        return Ok(false);
+    };
+
+    let phonemes = Sam::new(voice.clone()).phonetic_phonemes(phonetic).map_err(Error::Sam)?;
+
+    let psg_settings = psg::Settings {
+        frame_rate: cli.frame_rate,
+        ..psg::Settings::default()
+    };
+
+    let scc_settings = scc::Settings {
+        frame_rate: cli.frame_rate,
+        palette_size: cli.palette_size as usize,
+        waveform_hold: cli.waveform_hold,
+        ..scc::Settings::default()
+    };
+
+    let file = std::fs::File::create(path).map_err(|err| Error::IO("Could not create output file", err))?;
+    let mut writer = std::io::BufWriter::new(file);
+    let mut data = Vec::new();
+
+    let result = if cli.psg.is_some() {
+        psg::write_registers(&mut data, &psg::registers(&phonemes, voice, &psg_settings))
+            .and_then(|_| write_output(cli, &mut writer, &data, emit::Content::Psg, cli.frame_rate))
+    } else if cli.scc.is_some() {
+        scc::export(&phonemes, voice, &scc_settings).write(&mut data)
+            .and_then(|_| write_output(cli, &mut writer, &data, emit::Content::Scc, cli.frame_rate))
+    } else {
+        match cli.chip {
+            Chip::Psg => vgm::write_psg(&mut writer, &psg::registers(&phonemes, voice, &psg_settings), &psg_settings, cli.looping),
+            Chip::Scc => vgm::write_scc(&mut writer, &scc::export(&phonemes, voice, &scc_settings), &scc_settings, cli.looping)
+        }
+    };
+
+    result
+        .and_then(|_| writer.flush())
+        .map_err(|err| Error::IO("Could not write output file", err))?;
+
+    Ok(true)
+}
+
+// Write raw samples, a register dump or 4 bit samples as selected with --emit and --rle
+fn write_output<W: Write>(cli: &Cli, mut writer: W, data: &[u8], content: emit::Content, rate: u32) -> std::io::Result<()> {
+    let info = emit::Info {
+        content,
+        rate,
+        compression: if cli.rle { emit::Compression::Rle } else { emit::Compression::None }
+    };
+
+    match cli.emit {
+        Emit::Raw if cli.rle => writer.write_all(&emit::rle_compress(data)),
+        Emit::Raw => writer.write_all(data),
+        Emit::Binary => emit::write_binary(writer, data, &info),
+        Emit::Db => emit::write_db(writer, data, &info, &cli.label),
+        Emit::Basic => emit::write_basic(writer, data, &info, cli.basic_line)
+    }
+}
+
+fn run(cli: Cli) -> Result<(), Error> {
+    if cli.list_voices {
+        for preset in Preset::ALL {
+            let voice = Voice::preset(*preset);
+            println!("{:20} speed {:3}  pitch {:3}  throat {:3}  mouth {:3}", preset.name(), voice.speed(), voice.pitch(), voice.throat(), voice.mouth());
+        }
+
+        return Ok(());
+    }
+
+    let input = cli.input()?;
+
+    let phonetic = if cli.phonetic {
+        input
+    } else {
+        let dictionary = match &cli.dictionary {
+            Some(path) => Dictionary::load(path).map_err(Error::Dictionary)?,
+            None => Dictionary::new()
+        };
+
+        let mut reciter = Reciter::new()
+            .with_compatibility(cli.compatibility())
+            .with_dictionary(dictionary)
+            .with_normalization(cli.numbers);
+
+        if let Some(path) = &cli.rules {
+            reciter = reciter.with_rules(RuleSet::load(path).map_err(Error::Rules)?);
+        }
+
+        if cli.explain {
+            let explanation = reciter.explain(&input);
+            print!("{}", explanation);
+
+            return match explanation.error {
+                Some(err) => Err(Error::Sam(err.into())),
+                None => Ok(())
+            };
+        }
+
+        reciter
+            .text_to_phonemes(&input)
+            .map_err(|err| Error::Sam(err.into()))?
+    };
+
+    if cli.phonemes_only {
+        println!("{}", phonetic);
+        return Ok(());
+    }
+
+    let voice = cli.voice()?;
+
+    if Sam::new(voice.clone()).phonetic_phonemes(&phonetic).map_err(Error::Sam)?.is_empty() {
+        return Err(Error::Message("Nothing to say"));
+    }
+
+    if export(&cli, &voice, &phonetic)? {
+        return Ok(());
+    }
+
+    let sample_rate = voice.sample_rate();
+    let mut samples = Sam::new(voice).speak_phonetic_samples::<f32>(&phonetic).map_err(Error::Sam)?;
+
+    if let Some(gain) = cli.gain {
+        renderer::apply_gain(&mut samples, gain);
+    }
+
+    if cli.normalize {
+        renderer::normalize(&mut samples, 1.0);
+    }
+
+    if let Some(path) = &cli.pcm4 {
+        let settings = pcm::Settings {
+            curve: cli.curve.into(),
+            dither: cli.dither
+        };
+
+        let file = std::fs::File::create(path).map_err(|err| Error::IO("Could not create output file", err))?;
+        let mut writer = std::io::BufWriter::new(file);
+        write_output(&cli, &mut writer, &pcm::pack(&pcm::quantize(&samples, &settings)), emit::Content::Packed4, sample_rate)
+            .and_then(|_| writer.flush())
+            .map_err(|err| Error::IO("Could not write output file", err))?;
+    } else if let Some(path) = &cli.output {
+        let file = std::fs::File::create(path).map_err(|err| Error::IO("Could not create output file", err))?;
+        let mut writer = std::io::BufWriter::new(file);
+        wav::write_wav(&mut writer, &samples, sample_rate, cli.format.into())
+            .and_then(|_| writer.flush())
+            .map_err(|err| Error::IO("Could not write output file", err))?;
+    } else if cli.raw {
+        let mut stdout = std::io::BufWriter::new(std::io::stdout().lock());
+        let mut data = Vec::new();
+        wav::write_samples(&mut data, &samples, cli.format.into())
+            .and_then(|_| write_output(&cli, &mut stdout, &data, emit::Content::Samples(cli.format.into()), sample_rate))
+            .and_then(|_| stdout.flush())
+            .map_err(|err| Error::IO("Could not write to stdout", err))?;
+    } else {
+        #[cfg(feature = "playback")]
+        return play(&samples, sample_rate);
+
+        #[cfg(not(feature = "playback"))]
+        return Err(Error::Message("No output selected, use --output or --raw"));
+    }
+
+    Ok(())
+}
+
+fn main() {
+    if let Err(err) = run(Cli::parse()) {
+        eprintln!("Error: {}", err);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn verify_cli() {
+        use clap::CommandFactory;
+        super::Cli::command().debug_assert()
+    }
+}
@@ -1,13 +1,41 @@
-#[derive(Debug)]
+use crate::Compatibility;
+
+mod notation;
+mod phoneme_kind;
+
+pub use notation::{Notation, to_sam_string};
+pub use phoneme_kind::{Articulation, PhonemeKind};
+
+/// An error in the phonetic input. Positions are byte offsets into the input text.
+#[derive(Debug, Eq, PartialEq)]
 pub enum ParseError {
-    // TODO: Cases
+    UnknownPhoneme {
+        position: usize,
+        text: String
+    },
+    StressWithoutPhoneme {
+        position: usize
+    }
+}
+
+impl ParseError {
+    /// The byte offset of the offending characters in the input text.
+    pub fn position(&self) -> usize {
+        match self {
+            ParseError::UnknownPhoneme { position, .. } => *position,
+            ParseError::StressWithoutPhoneme { position } => *position
+        }
+    }
 }
 
 impl std::error::Error for ParseError {}
 
 impl std::fmt::Display for ParseError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "Parse error")
+        match self {
+            ParseError::UnknownPhoneme { position, text } => write!(f, "Unknown phoneme {:?} at position {}", text, position),
+            ParseError::StressWithoutPhoneme { position } => write!(f, "Stress marker at position {} does not follow a phoneme", position)
+        }
     }
 }
 
@@ -19,13 +47,78 @@ pub struct ParseResult {
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Phoneme {
     pub length: u8,
-    pub index: usize,
+    pub kind: PhonemeKind,
     pub stress: u8
 }
 
+/// Shorthands for the feature queries of [`PhonemeKind`].
 impl Phoneme {
     fn has_flag(&self, flag: u16) -> bool {
-        PHONEME_FLAGS[self.index] & flag != 0
+        self.kind.flags() & flag != 0
+    }
+
+    pub fn articulation(&self) -> Articulation {
+        self.kind.articulation()
+    }
+
+    pub fn is_pause(&self) -> bool {
+        self.kind.is_pause()
+    }
+
+    pub fn is_punctuation(&self) -> bool {
+        self.kind.is_punctuation()
+    }
+
+    pub fn is_glottal_closure(&self) -> bool {
+        self.kind.is_glottal_closure()
+    }
+
+    pub fn is_vowel(&self) -> bool {
+        self.kind.is_vowel()
+    }
+
+    pub fn is_diphthong(&self) -> bool {
+        self.kind.is_diphthong()
+    }
+
+    pub fn is_front_diphthong(&self) -> bool {
+        self.kind.is_front_diphthong()
+    }
+
+    pub fn is_consonant(&self) -> bool {
+        self.kind.is_consonant()
+    }
+
+    pub fn is_voiced(&self) -> bool {
+        self.kind.is_voiced()
+    }
+
+    pub fn is_stop(&self) -> bool {
+        self.kind.is_stop()
+    }
+
+    pub fn is_plosive(&self) -> bool {
+        self.kind.is_plosive()
+    }
+
+    pub fn is_unvoiced_plosive(&self) -> bool {
+        self.kind.is_unvoiced_plosive()
+    }
+
+    pub fn is_fricative(&self) -> bool {
+        self.kind.is_fricative()
+    }
+
+    pub fn is_liquid(&self) -> bool {
+        self.kind.is_liquid()
+    }
+
+    pub fn is_nasal(&self) -> bool {
+        self.kind.is_nasal()
+    }
+
+    pub fn is_alveolar(&self) -> bool {
+        self.kind.is_alveolar()
     }
 }
 
@@ -210,9 +303,11 @@ const PHONEME_LENGTH_TABLE: &[(u8, u8)] = &[
 ];
 
 mod flag {
-    // Unused constants
-    pub const _OX8000: u16          = 0x8000; // Unknown: ' *', '.*', '?*', ',*', '-*'
-    pub const _OX4000: u16          = 0x4000; // Unknown: '.*', '?*', ',*', '-*', 'Q*'
+    // Word and sentence breaks: the space and the punctuation phonemes
+    pub const PAUSE: u16            = 0x8000;
+
+    // The vocal folds are closed, cutting off the voice: the punctuation phonemes and 'Q*'
+    pub const GLOTTAL_CLOSURE: u16  = 0x4000;
 
     // Consonant articulations
     pub const FRICATIVE: u16        = 0x2000;
@@ -220,8 +315,8 @@ mod flag {
     pub const NASAL: u16            = 0x0800;
     pub const ALVEOLAR: u16         = 0x0400;
 
-    // 0x0200 is unused
-    pub const _OX0200: u16          = 0x0200;
+    // 0x0200 is not set for any phoneme, nor tested anywhere in the reference implementation
+    pub const _UNUSED: u16          = 0x0200;
 
     pub const PUNCTUATION: u16      = 0x0100;
     pub const VOWEL: u16            = 0x0080;
@@ -230,11 +325,9 @@ mod flag {
     pub const DIPHTHONG_YX: u16     = 0x0020; // Diphthong ending with YX, front vowels?
     pub const DIPHTHONG: u16        = 0x0010;
 
-    // Unknown:
-    // 'M*', 'N*', 'NX', 'DX', 'Q*', 'CH', 'J*', 'B*', '**', '**', 'D*',
-    // '**', '**', 'G*', '**', '**', 'GX', '**', '**', 'P*', '**', '**',
-    // 'T*', '**', '**', 'K*', '**', '**', 'KX', '**', '**'
-    pub const OX0008: u16           = 0x0008;
+    // The airflow through the mouth is completely blocked: the nasals, the plosives and their
+    // releases, the affricates 'CH' and 'J*', the flap 'DX' and the glottal stop 'Q*'
+    pub const STOP: u16             = 0x0008;
 
     pub const VOICED: u16           = 0x0004; // Applied to vowels and consonants
 
@@ -328,28 +421,27 @@ const PHONEME_FLAGS: &[u16] = &[
 ];
 
 /// Match both characters, but not with wildcards.
-fn full_match(sign1: char, sign2: char) -> Option<usize> {
+fn full_match(sign1: char, sign2: char) -> Option<PhonemeKind> {
     // TODO: Investigate if sign2 is ever an asterisk
     PHONEME_NAME_TABLE.iter().position(|(first, second)|
         *second != '*' && *first == sign1 && *second == sign2
-    )
+    ).and_then(PhonemeKind::from_index)
 }
 
 /// Match character plus a wildcard.
-fn wildcard_match(sign1: char) -> Option<usize> {
+fn wildcard_match(sign1: char) -> Option<PhonemeKind> {
     PHONEME_NAME_TABLE.iter().position(|(first, second)|
         *first == sign1 && *second == '*'
-    )
+    ).and_then(PhonemeKind::from_index)
 }
 
-// TODO: Emit Result instead of panicking
-fn parser1(text: &str) -> ParseResult {
+fn parser1(text: &str) -> Result<ParseResult, ParseError> {
     let mut result = ParseResult::new();
-    let mut iter = text.chars().peekable();
+    let mut iter = text.char_indices().peekable();
 
-    while let Some(sign1) = iter.next() {
-        if let Some(sign2) = iter.peek() {
-            if let Some(phoneme_index) = full_match(sign1, *sign2) {
+    while let Some((position, sign1)) = iter.next() {
+        if let Some((_, sign2)) = iter.peek() {
+            if let Some(kind) = full_match(sign1, *sign2) {
                 // Matched both characters (no wildcards)
 
                 // Skip the second character of the input as we've matched it
@@ -357,7 +449,7 @@ fn parser1(text: &str) -> ParseResult {
 
                 // add_phoneme
                 result.phonemes.push(Phoneme {
-                    index: phoneme_index,
+                    kind,
                     length: 0,
                     stress: 0
                 });
@@ -366,12 +458,12 @@ fn parser1(text: &str) -> ParseResult {
             }
         }
 
-        if let Some(phoneme_index) = wildcard_match(sign1) {
+        if let Some(kind) = wildcard_match(sign1) {
             // Matched just the first character (with second character matching '*'
 
             // add_phoneme
             result.phonemes.push(Phoneme {
-                index: phoneme_index,
+                kind,
                 length: 0,
                 stress: 0
             });
@@ -393,73 +485,44 @@ fn parser1(text: &str) -> ParseResult {
             //}
 
             // Set stress for prior phoneme
-            result.phonemes.last_mut().expect("Tried adding stress without adding a phoneme first").stress = index as u8;
+            result.phonemes.last_mut().ok_or(ParseError::StressWithoutPhoneme { position })?.stress = index as u8;
         } else {
-            panic!("Could not parse character {:?}", sign1);
+            return Err(ParseError::UnknownPhoneme {
+                position,
+                text: sign1.to_string()
+            });
         }
     }
 
-    result
+    Ok(result)
 }
 
-pub const PHONEME_PAUSE: usize         = 0;
-pub const PHONEME_PERIOD: usize        = 1;
-pub const PHONEME_QUESTION_MARK: usize = 2;
-pub const PHONEME_AX: usize            = 13;
-pub const PHONEME_UX: usize            = 16;
-pub const PHONEME_RX: usize            = 18;
-pub const PHONEME_LX: usize            = 19;
-pub const PHONEME_WX: usize            = 20;
-pub const PHONEME_YX: usize            = 21;
-pub const PHONEME_R_STAR: usize        = 23;
-pub const PHONEME_L_STAR: usize        = 24;
-pub const PHONEME_M_STAR: usize        = 27;
-pub const PHONEME_N_STAR: usize        = 28;
-pub const PHONEME_DX: usize            = 30;
-pub const PHONEME_Q_STAR: usize        = 31;
-pub const PHONEME_S_STAR: usize        = 32;
-pub const PHONEME_SLASH_H: usize       = 36;
-pub const PHONEME_SLASH_X: usize       = 37;
-pub const PHONEME_Z_STAR: usize        = 38;
-pub const PHONEME_CH: usize            = 42;
-pub const PHONEME_STAR_STAR_43: usize  = 43;
-pub const PHONEME_J_STAR: usize        = 44;
-pub const PHONEME_STAR_STAR_45: usize  = 45;
-pub const PHONEME_UW: usize            = 53;
-pub const PHONEME_D_STAR: usize        = 57;
-pub const PHONEME_G_STAR: usize        = 60;
-pub const PHONEME_GX: usize            = 63;
-pub const PHONEME_T_STAR: usize        = 69;
-pub const PHONEME_K_STAR: usize        = 72;
-pub const PHONEME_KX: usize            = 75;
-pub const PHONEME_UL: usize            = 78;
-pub const PHONEME_UM: usize            = 79;
-pub const PHONEME_UN: usize            = 80;
-
 fn handle_uw_ch_j(phonemes: &mut Vec<Phoneme>, position: usize) {
     let phoneme = &phonemes[position];
 
-    match phoneme.index {
+    match phoneme.kind {
         // 'UW' Example: NEW, DEW, SUE, ZOO, THOO, TOO
         // Check for UW with alveolar flag set on previous phoneme
-        PHONEME_UW if phonemes.get(position - 1).unwrap().has_flag(flag::ALVEOLAR) => {
-            phonemes[position].index = PHONEME_UX;
+        // Note: there is no previous phoneme at the start of the input, which the reference
+        // implementation treats as not alveolar.
+        PhonemeKind::Uw if position.checked_sub(1).is_some_and(|position| phonemes[position].has_flag(flag::ALVEOLAR)) => {
+            phonemes[position].kind = PhonemeKind::Ux;
         },
 
         // 'CH' Example: CHEW
-        PHONEME_CH => {
+        PhonemeKind::Ch => {
             phonemes.insert(position + 1, Phoneme {
                 length: 0,
-                index: PHONEME_STAR_STAR_43,
+                kind: PhonemeKind::ChRelease,
                 stress: phoneme.stress
             });
         },
 
         // 'J*' Example: JAY
-        PHONEME_J_STAR => {
+        PhonemeKind::J => {
             phonemes.insert(position + 1, Phoneme {
                 length: 0,
-                index: PHONEME_STAR_STAR_45,
+                kind: PhonemeKind::JRelease,
                 stress: phoneme.stress
             });
         },
@@ -468,7 +531,11 @@ fn handle_uw_ch_j(phonemes: &mut Vec<Phoneme>, position: usize) {
     }
 }
 
-fn parser2(result: &mut ParseResult) -> Result<(), ParseError> {
+fn prior_phoneme_kind(phonemes: &[Phoneme], position: usize) -> Option<PhonemeKind> {
+    position.checked_sub(1).map(|position| phonemes[position].kind)
+}
+
+fn parser2(result: &mut ParseResult, compatibility: Compatibility) -> Result<(), ParseError> {
     let mut position: isize = -1;
 
     loop {
@@ -480,7 +547,7 @@ fn parser2(result: &mut ParseResult) -> Result<(), ParseError> {
         }
 
         // Is phoneme pause?
-        if result.phonemes[position].index == PHONEME_PAUSE {
+        if result.phonemes[position].kind == PhonemeKind::Pause {
             continue;
         }
 
@@ -493,10 +560,10 @@ fn parser2(result: &mut ParseResult) -> Result<(), ParseError> {
             // 'WX' = 20 'YX' = 21
             result.phonemes.insert(position + 1, Phoneme {
                 length: 0,
-                index: if result.phonemes[position].has_flag(flag::DIPHTHONG_YX) {
-                    PHONEME_YX
+                kind: if result.phonemes[position].has_flag(flag::DIPHTHONG_YX) {
+                    PhonemeKind::Yx
                 } else {
-                    PHONEME_WX
+                    PhonemeKind::Wx
                 },
                 stress: result.phonemes[position].stress
             });
@@ -505,38 +572,38 @@ fn parser2(result: &mut ParseResult) -> Result<(), ParseError> {
             continue;
         }
 
-        if result.phonemes[position].index == PHONEME_UL {
+        if result.phonemes[position].kind == PhonemeKind::Ul {
             // 'UL' => 'AX' 'L*'
             // Example: MEDDLE
-            result.phonemes[position].index = PHONEME_AX;
+            result.phonemes[position].kind = PhonemeKind::Ax;
             result.phonemes.insert(position + 1, Phoneme {
                 length: 0,
-                index: PHONEME_L_STAR,
+                kind: PhonemeKind::L,
                 stress: result.phonemes[position].stress
             });
 
             continue;
         }
 
-        if result.phonemes[position].index == PHONEME_UM {
+        if result.phonemes[position].kind == PhonemeKind::Um {
             // 'UM' => 'AX' 'M*'
             // Example: ASTRONOMY
-            result.phonemes[position].index = PHONEME_AX;
+            result.phonemes[position].kind = PhonemeKind::Ax;
             result.phonemes.insert(position + 1, Phoneme {
                 length: 0,
-                index: PHONEME_M_STAR,
+                kind: PhonemeKind::M,
                 stress: result.phonemes[position].stress
             });
 
             continue;
         }
 
-        if result.phonemes[position].index == PHONEME_UN {
+        if result.phonemes[position].kind == PhonemeKind::Un {
             // 'UN' => 'AX' 'N*'
-            result.phonemes[position].index = PHONEME_AX;
+            result.phonemes[position].kind = PhonemeKind::Ax;
             result.phonemes.insert(position + 1, Phoneme {
                 length: 0,
-                index: PHONEME_N_STAR,
+                kind: PhonemeKind::N,
                 stress: result.phonemes[position].stress
             });
 
@@ -548,13 +615,13 @@ fn parser2(result: &mut ParseResult) -> Result<(), ParseError> {
             // RULE:
             //       <STRESSED VOWEL> <SILENCE> <STRESSED VOWEL> -> <STRESSED VOWEL> <SILENCE> Q <VOWEL>
             // EXAMPLE: AWAY EIGHT
-            if result.phonemes.get(position + 1).map_or(false, |phoneme| phoneme.index == PHONEME_PAUSE) { // If following phoneme is a pause, get next
+            if result.phonemes.get(position + 1).is_some_and(|phoneme| phoneme.kind == PhonemeKind::Pause) { // If following phoneme is a pause, get next
                 if let Some(phoneme) = result.phonemes.get(position + 2) {
                     if phoneme.has_flag(flag::VOWEL) && phoneme.stress != 0 {
                         // Insert glottal stop between two stressed vowels with space between them
                         result.phonemes.insert(position + 2, Phoneme {
                             length: 0,
-                            index: PHONEME_Q_STAR,
+                            kind: PhonemeKind::Q,
                             stress: 0
                         });
                     }
@@ -571,27 +638,27 @@ fn parser2(result: &mut ParseResult) -> Result<(), ParseError> {
             None
         };
 
-        if result.phonemes[position].index == PHONEME_R_STAR {
+        if result.phonemes[position].kind == PhonemeKind::R {
             if let Some(prior_phoneme) = prior_phoneme {
                 // position - 1 is guaranteed to be valid inside this block
                 // Rules for phonemes before R
-                match prior_phoneme.index {
+                match prior_phoneme.kind {
                     // Example: TRACK
                     // T* R* -> CH R*
-                    PHONEME_T_STAR => {
-                        result.phonemes[position - 1].index = PHONEME_CH;
+                    PhonemeKind::T => {
+                        result.phonemes[position - 1].kind = PhonemeKind::Ch;
                     },
 
                     // Example: DRY
                     // D* R* -> J* R*
-                    PHONEME_D_STAR => {
-                        result.phonemes[position - 1].index = PHONEME_J_STAR;
+                    PhonemeKind::D => {
+                        result.phonemes[position - 1].kind = PhonemeKind::J;
                     }
 
                     // Example: ART
                     // <VOWEL> R* -> <VOWEL> RX
                     _ => if prior_phoneme.has_flag(flag::VOWEL) {
-                        result.phonemes[position].index = PHONEME_RX;
+                        result.phonemes[position].kind = PhonemeKind::Rx;
                     }
                 }
             }
@@ -600,25 +667,25 @@ fn parser2(result: &mut ParseResult) -> Result<(), ParseError> {
         }
 
         // 'L*'
-        if result.phonemes[position].index == PHONEME_L_STAR && prior_phoneme.map_or(false, |phoneme| phoneme.has_flag(flag::VOWEL)) {
+        if result.phonemes[position].kind == PhonemeKind::L && prior_phoneme.is_some_and(|phoneme| phoneme.has_flag(flag::VOWEL)) {
             // Example: ALL
             // <VOWEL> L* -> <VOWEL> LX
-            result.phonemes[position].index = PHONEME_LX;
+            result.phonemes[position].kind = PhonemeKind::Lx;
             continue;
         }
 
         // 'G*' 'S*'
-        if result.phonemes[position].index == PHONEME_S_STAR && prior_phoneme.map_or(false, |phoneme| phoneme.index == PHONEME_G_STAR) {
+        if result.phonemes[position].kind == PhonemeKind::S && prior_phoneme.is_some_and(|phoneme| phoneme.kind == PhonemeKind::G) {
             // G S -> G Z
             // Can't get to fire -
             //       1. The G -> GX rule intervenes
             //       2. Reciter already replaces GS -> GZ
-            result.phonemes[position].index = PHONEME_Z_STAR;
+            result.phonemes[position].kind = PhonemeKind::Z;
             continue;
         }
 
         // 'G*'
-        if result.phonemes[position].index == PHONEME_G_STAR {
+        if result.phonemes[position].kind == PhonemeKind::G {
             // G <VOWEL OR DIPHTHONG NOT ENDING WITH IY> -> GX <VOWEL OR DIPHTHONG NOT ENDING WITH IY>
             // Example: GO
             if let Some(phoneme) = result.phonemes.get(position + 1) {
@@ -626,7 +693,7 @@ fn parser2(result: &mut ParseResult) -> Result<(), ParseError> {
                 if !phoneme.has_flag(flag::DIPHTHONG_YX) {
                     // replace G with GX and continue processing next phoneme
                     // G <VOWEL OR DIPHTHONG NOT ENDING WITH IY> -> GX <VOWEL OR DIPHTHONG NOT ENDING WITH IY>
-                    result.phonemes[position].index = PHONEME_GX;
+                    result.phonemes[position].kind = PhonemeKind::Gx;
                 }
             }
 
@@ -634,24 +701,24 @@ fn parser2(result: &mut ParseResult) -> Result<(), ParseError> {
         }
 
         // 'K*'
-        if result.phonemes[position].index == PHONEME_K_STAR {
+        if result.phonemes[position].kind == PhonemeKind::K {
             // K <VOWEL OR DIPHTHONG NOT ENDING WITH IY> -> KX <VOWEL OR DIPHTHONG NOT ENDING WITH IY>
             // Example: COW
             // If at end, replace current phoneme with KX
             // Note: also applies when next phoneme is not DIPHTHONG_YX
-            if result.phonemes.get(position + 1).map_or(true, |phoneme| !phoneme.has_flag(flag::DIPHTHONG_YX)) {
+            if result.phonemes.get(position + 1).is_none_or(|phoneme| !phoneme.has_flag(flag::DIPHTHONG_YX)) {
                 // VOWELS AND DIPHTHONGS ENDING WITH IY SOUND flag set?
-                result.phonemes[position].index = PHONEME_KX;
+                result.phonemes[position].kind = PhonemeKind::Kx;
 
                 // TODO: Figure out what the impact of this change is and if it can ever match
                 // any rules below
                 // TODO: Can be removed safely after switching to using array indices?
-                //phoneme = PHONEME_KX;
+                //phoneme = PhonemeKind::Kx;
             }
         }
 
         // Replace with softer version?
-        if result.phonemes[position].has_flag(flag::UNVOICED_PLOSIVE) && result.phonemes.get(position - 1).map_or(false, |phoneme| phoneme.index == PHONEME_S_STAR) {
+        if result.phonemes[position].has_flag(flag::UNVOICED_PLOSIVE) && prior_phoneme_kind(&result.phonemes, position) == Some(PhonemeKind::S) {
             // 'S*'
             // RULE:
             //   'S*' 'P*' -> 'S*' 'B*'
@@ -661,31 +728,41 @@ fn parser2(result: &mut ParseResult) -> Result<(), ParseError> {
             //   'S*' 'UM' -> 'S*' '**'
             //   'S*' 'UN' -> 'S*' '**'
             // Examples: SPY, STY, SKY, SCOWL
-            result.phonemes[position].index -= 12;
+            if let Some(voiced) = result.phonemes[position].kind.voiced_plosive() {
+                result.phonemes[position].kind = voiced;
+            }
         } else if !result.phonemes[position].has_flag(flag::UNVOICED_PLOSIVE) {
             handle_uw_ch_j(&mut result.phonemes, position);
         }
 
         // 'T*', 'D*'
-        if result.phonemes[position].index == PHONEME_T_STAR || result.phonemes[position].index == PHONEME_D_STAR {
+        if result.phonemes[position].kind == PhonemeKind::T || result.phonemes[position].kind == PhonemeKind::D {
             // RULE: Soften T following vowel
             // NOTE: This rule fails for cases such as "ODD"
             //       <UNSTRESSED VOWEL> T <PAUSE> -> <UNSTRESSED VOWEL> DX <PAUSE>
             //       <UNSTRESSED VOWEL> D <PAUSE>  -> <UNSTRESSED VOWEL> DX <PAUSE>
             // Example: PARTY, TARDY
-            if let Some(prior_phoneme) = result.phonemes.get(position - 1) {
-                if prior_phoneme.has_flag(flag::VOWEL) {
+            if let Some(prior_position) = position.checked_sub(1) {
+                if result.phonemes[prior_position].has_flag(flag::VOWEL) {
                     let mut phoneme = result.phonemes.get(position + 1);
                     let next_phoneme = phoneme;
 
-                    if next_phoneme.is_some() && next_phoneme.unwrap().index == PHONEME_PAUSE {
+                    if next_phoneme.is_some() && next_phoneme.unwrap().kind == PhonemeKind::Pause {
                         phoneme = result.phonemes.get(position + 2);
                     }
 
+                    // The reference implementation checks the stress of the phoneme directly after
+                    // the T or D, which is the pause rather than the vowel following it if there
+                    // is one. Pauses are never stressed, so the stress check always passes then.
+                    let stress_phoneme = match compatibility {
+                        Compatibility::SamJs => next_phoneme,
+                        Compatibility::Corrected => phoneme
+                    };
+
                     if let Some(phoneme) = phoneme {
-                        if phoneme.has_flag(flag::VOWEL) && next_phoneme.map_or(false, |phoneme| phoneme.stress == 0) {
+                        if phoneme.has_flag(flag::VOWEL) && stress_phoneme.is_some_and(|phoneme| phoneme.stress == 0) {
                             // Soften T or D following vowel or ER and preceding a pause -> DX
-                            result.phonemes[position].index = PHONEME_DX;
+                            result.phonemes[position].kind = PhonemeKind::Dx;
                         }
                     }
                 }
@@ -729,14 +806,14 @@ fn set_phoneme_length(phonemes: &mut [Phoneme]) {
         let stress = phoneme.stress;
 
         if stress == 0 || stress > 0x7F {
-            phoneme.length = PHONEME_LENGTH_TABLE[phoneme.index].0;
+            phoneme.length = PHONEME_LENGTH_TABLE[phoneme.kind.index()].0;
         } else {
-            phoneme.length = PHONEME_LENGTH_TABLE[phoneme.index].1;
+            phoneme.length = PHONEME_LENGTH_TABLE[phoneme.kind.index()].1;
         }
     }
 }
 
-fn adjust_lengths(phonemes: &mut Vec<Phoneme>) {
+fn adjust_lengths(phonemes: &mut [Phoneme]) {
     // LENGTHEN VOWELS PRECEDING PUNCTUATION
     //
     // Search for punctuation. If found, back up to the first vowel, then
@@ -792,7 +869,7 @@ fn adjust_lengths(phonemes: &mut Vec<Phoneme>) {
             // not a consonant
             if !phonemes[position].has_flag(flag::CONSONANT) {
                 // 'RX' or 'LX'?
-                if phonemes[position].index == PHONEME_RX || phonemes[position].index == PHONEME_LX {
+                if phonemes[position].kind == PhonemeKind::Rx || phonemes[position].kind == PhonemeKind::Lx {
                     position += 1;
 
                     if phonemes.get(position).map(|phoneme| phoneme.has_flag(flag::CONSONANT)).unwrap_or(false) {
@@ -810,7 +887,7 @@ fn adjust_lengths(phonemes: &mut Vec<Phoneme>) {
             // FIXME: the case when phoneme === END is taken over by !phonemeHasFlag(phoneme, FLAG_CONSONANT)
             let flags = vowel_phoneme_position.map_or(
                 flag::CONSONANT | flag::UNVOICED_PLOSIVE,
-                |position| PHONEME_FLAGS[phonemes[position].index]
+                |position| phonemes[position].kind.flags()
             );
 
             // Unvoiced
@@ -870,7 +947,7 @@ fn adjust_lengths(phonemes: &mut Vec<Phoneme>) {
 
             // Move past silence
             let mut position = loop_position + 1;
-            while position < phonemes.len() && phonemes[position].index == PHONEME_PAUSE {
+            while position < phonemes.len() && phonemes[position].kind == PhonemeKind::Pause {
                 position += 1;
             }
 
@@ -914,29 +991,32 @@ fn prolong_plosives(phonemes: &mut Vec<Phoneme>) {
         // If plosive, move to next non-empty phoneme and validate the flags.
         if phonemes[position].has_flag(flag::UNVOICED_PLOSIVE) {
             let mut next_non_empty = position + 1;
-            while phonemes.get(next_non_empty).map_or(false, |phoneme| phoneme.index == PHONEME_PAUSE) {
+            while phonemes.get(next_non_empty).is_some_and(|phoneme| phoneme.kind == PhonemeKind::Pause) {
                 next_non_empty += 1;
             }
 
-            // If not END and either flag 0x0008 or '/H' or '/X'
+            // If not END and either a stop consonant or '/H' or '/X'
             if let Some(phoneme) = phonemes.get(next_non_empty) {
-                if phoneme.has_flag(flag::OX0008) || phoneme.index == PHONEME_SLASH_H || phoneme.index == PHONEME_SLASH_X {
+                if phoneme.has_flag(flag::STOP) || phoneme.kind == PhonemeKind::SlashH || phoneme.kind == PhonemeKind::SlashX {
                     position += 1;
                     continue;
                 }
             }
         }
 
+        let (first_release, second_release) = phonemes[position].kind.plosive_release()
+            .expect("Only plosives have the plosive flag set");
+
         phonemes.insert(position + 1, Phoneme {
-            index: phonemes[position].index + 1,
+            kind: first_release,
             stress: phonemes[position].stress,
-            length: PHONEME_LENGTH_TABLE[phonemes[position].index + 1].0
+            length: PHONEME_LENGTH_TABLE[first_release.index()].0
         });
 
         phonemes.insert(position + 2, Phoneme {
-            index: phonemes[position].index + 2,
+            kind: second_release,
             stress: phonemes[position].stress,
-            length: PHONEME_LENGTH_TABLE[phonemes[position].index + 2].0
+            length: PHONEME_LENGTH_TABLE[second_release.index()].0
         });
 
         position += 3;
@@ -944,13 +1024,18 @@ fn prolong_plosives(phonemes: &mut Vec<Phoneme>) {
 }
 
 pub fn parse_phonemes(text: &str) -> Result<Vec<Phoneme>, ParseError> {
+    parse_phonemes_with(text, Compatibility::SamJs)
+}
+
+/// Like [`parse_phonemes`], but optionally fixes the bugs of the reference implementation.
+pub fn parse_phonemes_with(text: &str, compatibility: Compatibility) -> Result<Vec<Phoneme>, ParseError> {
     // TODO: Find a better name for this
 
     // Parser1
-    let mut result = parser1(text);
+    let mut result = parser1(text)?;
 
     // Parser2
-    parser2(&mut result)?;
+    parser2(&mut result, compatibility)?;
 
     // CopyStress
     copy_stress(&mut result.phonemes);
@@ -965,7 +1050,50 @@ pub fn parse_phonemes(text: &str) -> Result<Vec<Phoneme>, ParseError> {
     prolong_plosives(&mut result.phonemes);
 
     // Filter pauses
-    result.phonemes.retain(|phoneme| phoneme.index != PHONEME_PAUSE);
+    result.phonemes.retain(|phoneme| phoneme.kind != PhonemeKind::Pause);
 
     Ok(result.phonemes)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_phoneme() {
+        assert_eq!(parse_phonemes("/HEHXOW5").unwrap_err(), ParseError::UnknownPhoneme {
+            position: 4,
+            text: "X".to_owned()
+        });
+
+        let err = parse_phonemes("AA\u{e9}").unwrap_err();
+        assert_eq!(err.position(), 2);
+        assert_eq!(err.to_string(), "Unknown phoneme \"\u{e9}\" at position 2");
+    }
+
+    #[test]
+    fn stress_without_phoneme() {
+        assert_eq!(parse_phonemes("4AA").unwrap_err(), ParseError::StressWithoutPhoneme {
+            position: 0
+        });
+    }
+
+    #[test]
+    fn short_input() {
+        // These used to look up a phoneme before the start of the input
+        for text in ["P", "T", "UW", "KAA5T"] {
+            assert!(parse_phonemes(text).is_ok(), "Could not parse {:?}", text);
+        }
+    }
+
+    #[test]
+    fn flap_before_stressed_vowel() {
+        let kinds = |compatibility| parse_phonemes_with("AAT AA5", compatibility).unwrap()
+            .iter()
+            .map(|phoneme| phoneme.kind)
+            .collect::<Vec<_>>();
+
+        assert_eq!(kinds(Compatibility::SamJs)[1], PhonemeKind::Dx);
+        assert_eq!(kinds(Compatibility::Corrected)[1], PhonemeKind::T);
+    }
+}
@@ -0,0 +1,142 @@
+use super::{Phoneme, PhonemeKind, STRESS_TABLE};
+
+/// Formats a phoneme sequence in SAM's phonetic notation, such as "/HEHLOW5".
+///
+/// The phonemes that the parser inserts by itself are left out, so that parsing the notation
+/// yields the same sequence again:
+///
+/// * The release states of plosives, CH and J*
+/// * The WX or YX glide following a diphthong
+/// * Stress that was copied from a vowel to the consonant before it
+///
+/// CH and J* without a release state stem from T* R* and D* R*, and are written as T and D.
+///
+/// Note that the parser drops the word breaks, which influence some of its rules. The notation of
+/// phonemes parsed from input with spaces runs the words together, which may parse differently.
+pub struct Notation<'a> {
+    phonemes: &'a [Phoneme],
+    lengths: bool
+}
+
+impl<'a> Notation<'a> {
+    pub fn new(phonemes: &'a [Phoneme]) -> Self {
+        Self {
+            phonemes,
+            lengths: false
+        }
+    }
+
+    /// Append the length of each phoneme in frames between parentheses, as in "/H(2)EH(8)LX(9)OW5(14)".
+    /// This is meant for inspecting the parser and can not be parsed back.
+    pub fn with_lengths(mut self) -> Self {
+        self.lengths = true;
+        self
+    }
+}
+
+impl std::fmt::Display for Notation<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let mut iter = self.phonemes.iter().enumerate().peekable();
+
+        while let Some((position, phoneme)) = iter.next() {
+            if phoneme.kind.is_internal() {
+                continue;
+            }
+
+            let next = self.phonemes.get(position + 1).map(|phoneme| phoneme.kind);
+
+            let name = match phoneme.kind {
+                PhonemeKind::Ch if next != Some(PhonemeKind::ChRelease) => "T",
+                PhonemeKind::J if next != Some(PhonemeKind::JRelease) => "D",
+                kind => kind.name().trim_end_matches('*')
+            };
+
+            write!(f, "{}", if name.is_empty() { " " } else { name })?;
+
+            if phoneme.stress != 0 && !is_copied_stress(self.phonemes, position) {
+                if let Some(stress) = STRESS_TABLE.get(phoneme.stress as usize) {
+                    write!(f, "{}", stress)?;
+                }
+            }
+
+            if self.lengths {
+                write!(f, "({})", phoneme.length)?;
+            }
+
+            // Skip the glide that the parser inserts after a diphthong
+            if phoneme.kind.is_diphthong() {
+                let glide = if phoneme.kind.is_front_diphthong() {
+                    PhonemeKind::Yx
+                } else {
+                    PhonemeKind::Wx
+                };
+
+                iter.next_if(|(_, phoneme)| phoneme.kind == glide);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Whether the stress of a consonant is the one copied from the stressed vowel that follows it. The
+// plosive releases are inserted after stress is copied, so they are skipped.
+fn is_copied_stress(phonemes: &[Phoneme], position: usize) -> bool {
+    let phoneme = &phonemes[position];
+
+    if !phoneme.kind.is_consonant() {
+        return false;
+    }
+
+    let next = phonemes[position + 1..].iter()
+        .find(|next| !(next.kind.is_internal() && next.kind.is_plosive()));
+
+    next.is_some_and(|next| next.kind.is_vowel() && next.stress != 0 && next.stress < 0x80 && phoneme.stress == next.stress + 1)
+}
+
+/// Format a phoneme sequence in SAM's phonetic notation, see [`Notation`].
+pub fn to_sam_string(phonemes: &[Phoneme]) -> String {
+    Notation::new(phonemes).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_phonemes;
+
+    #[test]
+    fn round_trip() {
+        let inputs = [
+            "/HEHLOW5",
+            "SPAY4KAA5T",
+            "TRAE4KDRAY5",
+            "CHEH3KJEY4",
+            "MEH4DUL",
+            "AESTRAA5NUMIY",
+            "SUW2NUW5DUW",
+            "PAA5RTIY.",
+            "GOW5GIY3KAW4KIY?",
+            "M5AAN1",
+            "OWWX"
+        ];
+
+        for input in inputs {
+            let phonemes = parse_phonemes(input).unwrap();
+            let notation = to_sam_string(&phonemes);
+
+            assert_eq!(parse_phonemes(&notation).unwrap(), phonemes, "{} -> {}", input, notation);
+        }
+    }
+
+    #[test]
+    fn notation() {
+        let phonemes = parse_phonemes("/HEHLOW5").unwrap();
+
+        // L* after a vowel becomes LX
+        assert_eq!(to_sam_string(&phonemes), "/HEHLXOW5");
+        assert_eq!(Notation::new(&phonemes).with_lengths().to_string(), "/H(2)EH(8)LX(9)OW5(14)");
+
+        // T* R* becomes CH R* without a release state, K* at the end becomes KX
+        assert_eq!(to_sam_string(&parse_phonemes("TRAE4K").unwrap()), "TRAE4KX");
+    }
+}
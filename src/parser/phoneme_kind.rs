@@ -0,0 +1,862 @@
+use super::{flag, ParseError, PHONEME_FLAGS, PHONEME_LENGTH_TABLE, PHONEME_NAME_TABLE};
+
+/// A SAM phoneme.
+///
+/// Besides the phonemes that can be written in phonetic input, this includes the internal states
+/// that the parser inserts after plosives and affricates to render their release.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum PhonemeKind {
+    /// ` *` A pause between words
+    Pause,
+
+    /// `.*` Period, ends a sentence with falling inflection
+    Period,
+
+    /// `?*` Question mark, ends a sentence with rising inflection
+    QuestionMark,
+
+    /// `,*` Comma
+    Comma,
+
+    /// `-*` Dash
+    Dash,
+
+    /// `IY` As in beet
+    Iy,
+
+    /// `IH` As in bit
+    Ih,
+
+    /// `EH` As in bet
+    Eh,
+
+    /// `AE` As in bat
+    Ae,
+
+    /// `AA` As in hot
+    Aa,
+
+    /// `AH` As in but
+    Ah,
+
+    /// `AO` As in bought
+    Ao,
+
+    /// `UH` As in book
+    Uh,
+
+    /// `AX` As in about
+    Ax,
+
+    /// `IX` As in solid
+    Ix,
+
+    /// `ER` As in bird
+    Er,
+
+    /// `UX` As in new, UW after an alveolar
+    Ux,
+
+    /// `OH` As in cone
+    Oh,
+
+    /// `RX` R after a vowel
+    Rx,
+
+    /// `LX` L after a vowel
+    Lx,
+
+    /// `WX` Glide at the end of a diphthong ending in W
+    Wx,
+
+    /// `YX` Glide at the end of a diphthong ending in Y
+    Yx,
+
+    /// `WH` As in why
+    Wh,
+
+    /// `R*` As in red
+    R,
+
+    /// `L*` As in allow
+    L,
+
+    /// `W*` As in away
+    W,
+
+    /// `Y*` As in yellow
+    Y,
+
+    /// `M*` As in men
+    M,
+
+    /// `N*` As in no
+    N,
+
+    /// `NX` As in sing
+    Nx,
+
+    /// `DX` As in butter, a flapped T or D
+    Dx,
+
+    /// `Q*` Glottal stop
+    Q,
+
+    /// `S*` As in sail
+    S,
+
+    /// `SH` As in ship
+    Sh,
+
+    /// `F*` As in fed
+    F,
+
+    /// `TH` As in thin
+    Th,
+
+    /// `/H` As in hat
+    SlashH,
+
+    /// `/X` As in loch
+    SlashX,
+
+    /// `Z*` As in has
+    Z,
+
+    /// `ZH` As in pleasure
+    Zh,
+
+    /// `V*` As in very
+    V,
+
+    /// `DH` As in then
+    Dh,
+
+    /// `CH` As in check
+    Ch,
+
+    /// `**` Release of CH, inserted by the parser
+    ChRelease,
+
+    /// `J*` As in judge
+    J,
+
+    /// `**` Release of J*, inserted by the parser
+    JRelease,
+
+    /// `EY` As in made
+    Ey,
+
+    /// `AY` As in high
+    Ay,
+
+    /// `OY` As in boy
+    Oy,
+
+    /// `AW` As in how
+    Aw,
+
+    /// `OW` As in slow
+    Ow,
+
+    /// `UW` As in crew
+    Uw,
+
+    /// `B*` As in bad
+    B,
+
+    /// `**` First release state of B*, inserted by the parser
+    BRelease1,
+
+    /// `**` Second release state of B*, inserted by the parser
+    BRelease2,
+
+    /// `D*` As in dog
+    D,
+
+    /// `**` First release state of D*, inserted by the parser
+    DRelease1,
+
+    /// `**` Second release state of D*, inserted by the parser
+    DRelease2,
+
+    /// `G*` As in again
+    G,
+
+    /// `**` First release state of G*, inserted by the parser
+    GRelease1,
+
+    /// `**` Second release state of G*, inserted by the parser
+    GRelease2,
+
+    /// `GX` As in get, a G not followed by a front vowel
+    Gx,
+
+    /// `**` First release state of GX, inserted by the parser
+    GxRelease1,
+
+    /// `**` Second release state of GX, inserted by the parser
+    GxRelease2,
+
+    /// `P*` As in pack
+    P,
+
+    /// `**` First release state of P*, inserted by the parser
+    PRelease1,
+
+    /// `**` Second release state of P*, inserted by the parser
+    PRelease2,
+
+    /// `T*` As in tack
+    T,
+
+    /// `**` First release state of T*, inserted by the parser
+    TRelease1,
+
+    /// `**` Second release state of T*, inserted by the parser
+    TRelease2,
+
+    /// `K*` As in kit
+    K,
+
+    /// `**` First release state of K*, inserted by the parser
+    KRelease1,
+
+    /// `**` Second release state of K*, inserted by the parser
+    KRelease2,
+
+    /// `KX` As in comb, a K not followed by a front vowel
+    Kx,
+
+    /// `**` First release state of KX, inserted by the parser
+    KxRelease1,
+
+    /// `**` Second release state of KX, inserted by the parser
+    KxRelease2,
+
+    /// `UL` Syllabic L, replaced with AX L* by the parser
+    Ul,
+
+    /// `UM` Syllabic M, replaced with AX M* by the parser
+    Um,
+
+    /// `UN` Syllabic N, replaced with AX N* by the parser
+    Un
+}
+
+/// The manner in which a phoneme is articulated, derived from its flags.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Articulation {
+    /// A word break or punctuation, rendered as silence
+    Pause,
+
+    /// A vowel, including the syllabic UL, UM and UN
+    Vowel,
+
+    /// A vowel that glides into WX or YX
+    Diphthong,
+
+    /// R*, L*, W* and Y*
+    Liquid,
+
+    /// M*, N* and NX
+    Nasal,
+
+    /// A plosive or one of its release states
+    Plosive,
+
+    /// CH and J*, whose release states are fricatives
+    Affricate,
+
+    /// DX
+    Flap,
+
+    /// Q*
+    GlottalStop,
+
+    /// S*, SH, F*, TH, Z*, ZH, V* and DH
+    Fricative,
+
+    /// WH, /H and /X, which take on the formants of the surrounding phonemes
+    Aspirate
+}
+
+impl PhonemeKind {
+    /// All phonemes, in the order of SAM's phoneme tables.
+    pub const ALL: &'static [PhonemeKind] = &[
+        PhonemeKind::Pause, PhonemeKind::Period, PhonemeKind::QuestionMark, PhonemeKind::Comma,
+        PhonemeKind::Dash, PhonemeKind::Iy, PhonemeKind::Ih, PhonemeKind::Eh,
+        PhonemeKind::Ae, PhonemeKind::Aa, PhonemeKind::Ah, PhonemeKind::Ao,
+        PhonemeKind::Uh, PhonemeKind::Ax, PhonemeKind::Ix, PhonemeKind::Er,
+        PhonemeKind::Ux, PhonemeKind::Oh, PhonemeKind::Rx, PhonemeKind::Lx,
+        PhonemeKind::Wx, PhonemeKind::Yx, PhonemeKind::Wh, PhonemeKind::R,
+        PhonemeKind::L, PhonemeKind::W, PhonemeKind::Y, PhonemeKind::M,
+        PhonemeKind::N, PhonemeKind::Nx, PhonemeKind::Dx, PhonemeKind::Q,
+        PhonemeKind::S, PhonemeKind::Sh, PhonemeKind::F, PhonemeKind::Th,
+        PhonemeKind::SlashH, PhonemeKind::SlashX, PhonemeKind::Z, PhonemeKind::Zh,
+        PhonemeKind::V, PhonemeKind::Dh, PhonemeKind::Ch, PhonemeKind::ChRelease,
+        PhonemeKind::J, PhonemeKind::JRelease, PhonemeKind::Ey, PhonemeKind::Ay,
+        PhonemeKind::Oy, PhonemeKind::Aw, PhonemeKind::Ow, PhonemeKind::Uw,
+        PhonemeKind::B, PhonemeKind::BRelease1, PhonemeKind::BRelease2, PhonemeKind::D,
+        PhonemeKind::DRelease1, PhonemeKind::DRelease2, PhonemeKind::G, PhonemeKind::GRelease1,
+        PhonemeKind::GRelease2, PhonemeKind::Gx, PhonemeKind::GxRelease1, PhonemeKind::GxRelease2,
+        PhonemeKind::P, PhonemeKind::PRelease1, PhonemeKind::PRelease2, PhonemeKind::T,
+        PhonemeKind::TRelease1, PhonemeKind::TRelease2, PhonemeKind::K, PhonemeKind::KRelease1,
+        PhonemeKind::KRelease2, PhonemeKind::Kx, PhonemeKind::KxRelease1, PhonemeKind::KxRelease2,
+        PhonemeKind::Ul, PhonemeKind::Um, PhonemeKind::Un
+    ];
+
+    /// Look up a phoneme by its position in SAM's phoneme tables.
+    ///
+    /// Positions 46 and 47 hold two unnamed entries that the parser never produces, these return
+    /// `None`.
+    pub fn from_index(index: usize) -> Option<Self> {
+        match index {
+            0 => Some(PhonemeKind::Pause),
+            1 => Some(PhonemeKind::Period),
+            2 => Some(PhonemeKind::QuestionMark),
+            3 => Some(PhonemeKind::Comma),
+            4 => Some(PhonemeKind::Dash),
+            5 => Some(PhonemeKind::Iy),
+            6 => Some(PhonemeKind::Ih),
+            7 => Some(PhonemeKind::Eh),
+            8 => Some(PhonemeKind::Ae),
+            9 => Some(PhonemeKind::Aa),
+            10 => Some(PhonemeKind::Ah),
+            11 => Some(PhonemeKind::Ao),
+            12 => Some(PhonemeKind::Uh),
+            13 => Some(PhonemeKind::Ax),
+            14 => Some(PhonemeKind::Ix),
+            15 => Some(PhonemeKind::Er),
+            16 => Some(PhonemeKind::Ux),
+            17 => Some(PhonemeKind::Oh),
+            18 => Some(PhonemeKind::Rx),
+            19 => Some(PhonemeKind::Lx),
+            20 => Some(PhonemeKind::Wx),
+            21 => Some(PhonemeKind::Yx),
+            22 => Some(PhonemeKind::Wh),
+            23 => Some(PhonemeKind::R),
+            24 => Some(PhonemeKind::L),
+            25 => Some(PhonemeKind::W),
+            26 => Some(PhonemeKind::Y),
+            27 => Some(PhonemeKind::M),
+            28 => Some(PhonemeKind::N),
+            29 => Some(PhonemeKind::Nx),
+            30 => Some(PhonemeKind::Dx),
+            31 => Some(PhonemeKind::Q),
+            32 => Some(PhonemeKind::S),
+            33 => Some(PhonemeKind::Sh),
+            34 => Some(PhonemeKind::F),
+            35 => Some(PhonemeKind::Th),
+            36 => Some(PhonemeKind::SlashH),
+            37 => Some(PhonemeKind::SlashX),
+            38 => Some(PhonemeKind::Z),
+            39 => Some(PhonemeKind::Zh),
+            40 => Some(PhonemeKind::V),
+            41 => Some(PhonemeKind::Dh),
+            42 => Some(PhonemeKind::Ch),
+            43 => Some(PhonemeKind::ChRelease),
+            44 => Some(PhonemeKind::J),
+            45 => Some(PhonemeKind::JRelease),
+            48 => Some(PhonemeKind::Ey),
+            49 => Some(PhonemeKind::Ay),
+            50 => Some(PhonemeKind::Oy),
+            51 => Some(PhonemeKind::Aw),
+            52 => Some(PhonemeKind::Ow),
+            53 => Some(PhonemeKind::Uw),
+            54 => Some(PhonemeKind::B),
+            55 => Some(PhonemeKind::BRelease1),
+            56 => Some(PhonemeKind::BRelease2),
+            57 => Some(PhonemeKind::D),
+            58 => Some(PhonemeKind::DRelease1),
+            59 => Some(PhonemeKind::DRelease2),
+            60 => Some(PhonemeKind::G),
+            61 => Some(PhonemeKind::GRelease1),
+            62 => Some(PhonemeKind::GRelease2),
+            63 => Some(PhonemeKind::Gx),
+            64 => Some(PhonemeKind::GxRelease1),
+            65 => Some(PhonemeKind::GxRelease2),
+            66 => Some(PhonemeKind::P),
+            67 => Some(PhonemeKind::PRelease1),
+            68 => Some(PhonemeKind::PRelease2),
+            69 => Some(PhonemeKind::T),
+            70 => Some(PhonemeKind::TRelease1),
+            71 => Some(PhonemeKind::TRelease2),
+            72 => Some(PhonemeKind::K),
+            73 => Some(PhonemeKind::KRelease1),
+            74 => Some(PhonemeKind::KRelease2),
+            75 => Some(PhonemeKind::Kx),
+            76 => Some(PhonemeKind::KxRelease1),
+            77 => Some(PhonemeKind::KxRelease2),
+            78 => Some(PhonemeKind::Ul),
+            79 => Some(PhonemeKind::Um),
+            80 => Some(PhonemeKind::Un),
+            _ => None
+        }
+    }
+
+    /// The position of the phoneme in SAM's phoneme tables.
+    pub fn index(&self) -> usize {
+        match self {
+            PhonemeKind::Pause => 0,
+            PhonemeKind::Period => 1,
+            PhonemeKind::QuestionMark => 2,
+            PhonemeKind::Comma => 3,
+            PhonemeKind::Dash => 4,
+            PhonemeKind::Iy => 5,
+            PhonemeKind::Ih => 6,
+            PhonemeKind::Eh => 7,
+            PhonemeKind::Ae => 8,
+            PhonemeKind::Aa => 9,
+            PhonemeKind::Ah => 10,
+            PhonemeKind::Ao => 11,
+            PhonemeKind::Uh => 12,
+            PhonemeKind::Ax => 13,
+            PhonemeKind::Ix => 14,
+            PhonemeKind::Er => 15,
+            PhonemeKind::Ux => 16,
+            PhonemeKind::Oh => 17,
+            PhonemeKind::Rx => 18,
+            PhonemeKind::Lx => 19,
+            PhonemeKind::Wx => 20,
+            PhonemeKind::Yx => 21,
+            PhonemeKind::Wh => 22,
+            PhonemeKind::R => 23,
+            PhonemeKind::L => 24,
+            PhonemeKind::W => 25,
+            PhonemeKind::Y => 26,
+            PhonemeKind::M => 27,
+            PhonemeKind::N => 28,
+            PhonemeKind::Nx => 29,
+            PhonemeKind::Dx => 30,
+            PhonemeKind::Q => 31,
+            PhonemeKind::S => 32,
+            PhonemeKind::Sh => 33,
+            PhonemeKind::F => 34,
+            PhonemeKind::Th => 35,
+            PhonemeKind::SlashH => 36,
+            PhonemeKind::SlashX => 37,
+            PhonemeKind::Z => 38,
+            PhonemeKind::Zh => 39,
+            PhonemeKind::V => 40,
+            PhonemeKind::Dh => 41,
+            PhonemeKind::Ch => 42,
+            PhonemeKind::ChRelease => 43,
+            PhonemeKind::J => 44,
+            PhonemeKind::JRelease => 45,
+            PhonemeKind::Ey => 48,
+            PhonemeKind::Ay => 49,
+            PhonemeKind::Oy => 50,
+            PhonemeKind::Aw => 51,
+            PhonemeKind::Ow => 52,
+            PhonemeKind::Uw => 53,
+            PhonemeKind::B => 54,
+            PhonemeKind::BRelease1 => 55,
+            PhonemeKind::BRelease2 => 56,
+            PhonemeKind::D => 57,
+            PhonemeKind::DRelease1 => 58,
+            PhonemeKind::DRelease2 => 59,
+            PhonemeKind::G => 60,
+            PhonemeKind::GRelease1 => 61,
+            PhonemeKind::GRelease2 => 62,
+            PhonemeKind::Gx => 63,
+            PhonemeKind::GxRelease1 => 64,
+            PhonemeKind::GxRelease2 => 65,
+            PhonemeKind::P => 66,
+            PhonemeKind::PRelease1 => 67,
+            PhonemeKind::PRelease2 => 68,
+            PhonemeKind::T => 69,
+            PhonemeKind::TRelease1 => 70,
+            PhonemeKind::TRelease2 => 71,
+            PhonemeKind::K => 72,
+            PhonemeKind::KRelease1 => 73,
+            PhonemeKind::KRelease2 => 74,
+            PhonemeKind::Kx => 75,
+            PhonemeKind::KxRelease1 => 76,
+            PhonemeKind::KxRelease2 => 77,
+            PhonemeKind::Ul => 78,
+            PhonemeKind::Um => 79,
+            PhonemeKind::Un => 80
+        }
+    }
+
+    /// Look up a phoneme by its two character SAM name, such as "IY", "/H" or "R*". The trailing
+    /// "*" of single character names may be omitted. The internal states, which are named "**",
+    /// can not be looked up.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let mut characters = name.chars();
+        let first = characters.next()?;
+        let second = characters.next().unwrap_or('*');
+
+        if first == '*' || characters.next().is_some() {
+            return None;
+        }
+
+        PHONEME_NAME_TABLE.iter()
+            .position(|candidate| *candidate == (first, second))
+            .and_then(PhonemeKind::from_index)
+    }
+
+    /// The two character SAM name of the phoneme, such as "IY", "/H" or "R*". Internal states are
+    /// named "**".
+    pub fn name(&self) -> &'static str {
+        match self {
+            PhonemeKind::Pause => " *",
+            PhonemeKind::Period => ".*",
+            PhonemeKind::QuestionMark => "?*",
+            PhonemeKind::Comma => ",*",
+            PhonemeKind::Dash => "-*",
+            PhonemeKind::Iy => "IY",
+            PhonemeKind::Ih => "IH",
+            PhonemeKind::Eh => "EH",
+            PhonemeKind::Ae => "AE",
+            PhonemeKind::Aa => "AA",
+            PhonemeKind::Ah => "AH",
+            PhonemeKind::Ao => "AO",
+            PhonemeKind::Uh => "UH",
+            PhonemeKind::Ax => "AX",
+            PhonemeKind::Ix => "IX",
+            PhonemeKind::Er => "ER",
+            PhonemeKind::Ux => "UX",
+            PhonemeKind::Oh => "OH",
+            PhonemeKind::Rx => "RX",
+            PhonemeKind::Lx => "LX",
+            PhonemeKind::Wx => "WX",
+            PhonemeKind::Yx => "YX",
+            PhonemeKind::Wh => "WH",
+            PhonemeKind::R => "R*",
+            PhonemeKind::L => "L*",
+            PhonemeKind::W => "W*",
+            PhonemeKind::Y => "Y*",
+            PhonemeKind::M => "M*",
+            PhonemeKind::N => "N*",
+            PhonemeKind::Nx => "NX",
+            PhonemeKind::Dx => "DX",
+            PhonemeKind::Q => "Q*",
+            PhonemeKind::S => "S*",
+            PhonemeKind::Sh => "SH",
+            PhonemeKind::F => "F*",
+            PhonemeKind::Th => "TH",
+            PhonemeKind::SlashH => "/H",
+            PhonemeKind::SlashX => "/X",
+            PhonemeKind::Z => "Z*",
+            PhonemeKind::Zh => "ZH",
+            PhonemeKind::V => "V*",
+            PhonemeKind::Dh => "DH",
+            PhonemeKind::Ch => "CH",
+            PhonemeKind::ChRelease => "**",
+            PhonemeKind::J => "J*",
+            PhonemeKind::JRelease => "**",
+            PhonemeKind::Ey => "EY",
+            PhonemeKind::Ay => "AY",
+            PhonemeKind::Oy => "OY",
+            PhonemeKind::Aw => "AW",
+            PhonemeKind::Ow => "OW",
+            PhonemeKind::Uw => "UW",
+            PhonemeKind::B => "B*",
+            PhonemeKind::BRelease1 => "**",
+            PhonemeKind::BRelease2 => "**",
+            PhonemeKind::D => "D*",
+            PhonemeKind::DRelease1 => "**",
+            PhonemeKind::DRelease2 => "**",
+            PhonemeKind::G => "G*",
+            PhonemeKind::GRelease1 => "**",
+            PhonemeKind::GRelease2 => "**",
+            PhonemeKind::Gx => "GX",
+            PhonemeKind::GxRelease1 => "**",
+            PhonemeKind::GxRelease2 => "**",
+            PhonemeKind::P => "P*",
+            PhonemeKind::PRelease1 => "**",
+            PhonemeKind::PRelease2 => "**",
+            PhonemeKind::T => "T*",
+            PhonemeKind::TRelease1 => "**",
+            PhonemeKind::TRelease2 => "**",
+            PhonemeKind::K => "K*",
+            PhonemeKind::KRelease1 => "**",
+            PhonemeKind::KRelease2 => "**",
+            PhonemeKind::Kx => "KX",
+            PhonemeKind::KxRelease1 => "**",
+            PhonemeKind::KxRelease2 => "**",
+            PhonemeKind::Ul => "UL",
+            PhonemeKind::Um => "UM",
+            PhonemeKind::Un => "UN"
+        }
+    }
+
+    /// The raw flags of the phoneme as they appear in the reference implementation. Prefer the
+    /// typed queries below.
+    pub fn flags(&self) -> u16 {
+        PHONEME_FLAGS[self.index()]
+    }
+
+    fn has_flag(&self, flag: u16) -> bool {
+        self.flags() & flag != 0
+    }
+
+    /// The manner in which the phoneme is articulated.
+    pub fn articulation(&self) -> Articulation {
+        if self.is_pause() {
+            Articulation::Pause
+        } else if self.is_diphthong() {
+            Articulation::Diphthong
+        } else if self.is_vowel() {
+            Articulation::Vowel
+        } else if self.is_nasal() {
+            Articulation::Nasal
+        } else if self.is_liquid() {
+            Articulation::Liquid
+        } else if self.is_plosive() {
+            Articulation::Plosive
+        } else if self.is_glottal_closure() {
+            Articulation::GlottalStop
+        } else if *self == PhonemeKind::Dx {
+            Articulation::Flap
+        } else if self.is_stop() {
+            // Only CH and J* remain
+            Articulation::Affricate
+        } else if self.is_fricative() {
+            Articulation::Fricative
+        } else {
+            Articulation::Aspirate
+        }
+    }
+
+    /// A word break (" ") or punctuation. These are rendered as silence.
+    pub fn is_pause(&self) -> bool {
+        self.has_flag(flag::PAUSE)
+    }
+
+    /// One of the punctuation phonemes ".", "?", "," and "-".
+    pub fn is_punctuation(&self) -> bool {
+        self.has_flag(flag::PUNCTUATION)
+    }
+
+    /// The voice is cut off by closing the vocal folds, which is the case for punctuation and the
+    /// glottal stop Q*.
+    pub fn is_glottal_closure(&self) -> bool {
+        self.has_flag(flag::GLOTTAL_CLOSURE)
+    }
+
+    /// A vowel or diphthong. Note that the syllabic UL, UM and UN are vowels as well, and that UM
+    /// and UN are also consonants.
+    pub fn is_vowel(&self) -> bool {
+        self.has_flag(flag::VOWEL)
+    }
+
+    /// A vowel that glides into WX or YX, such as EY and OW.
+    pub fn is_diphthong(&self) -> bool {
+        self.has_flag(flag::DIPHTHONG)
+    }
+
+    /// A diphthong that glides into YX (EY, AY and OY), rather than into WX.
+    pub fn is_front_diphthong(&self) -> bool {
+        self.has_flag(flag::DIPHTHONG_YX)
+    }
+
+    pub fn is_consonant(&self) -> bool {
+        self.has_flag(flag::CONSONANT)
+    }
+
+    /// Whether the vocal folds vibrate. Note that the release states of voiced plosives are
+    /// voiced as well.
+    pub fn is_voiced(&self) -> bool {
+        self.has_flag(flag::VOICED)
+    }
+
+    /// The airflow through the mouth is blocked completely: nasals, plosives, affricates, the
+    /// flap DX and the glottal stop Q*.
+    pub fn is_stop(&self) -> bool {
+        self.has_flag(flag::STOP)
+    }
+
+    /// A voiced or unvoiced plosive, or one of the release states the parser inserts after it.
+    pub fn is_plosive(&self) -> bool {
+        self.has_flag(flag::PLOSIVE)
+    }
+
+    /// P*, T*, K* and KX and their release states. UM and UN are flagged as unvoiced plosives
+    /// too, but are not plosives.
+    pub fn is_unvoiced_plosive(&self) -> bool {
+        self.has_flag(flag::UNVOICED_PLOSIVE)
+    }
+
+    pub fn is_fricative(&self) -> bool {
+        self.has_flag(flag::FRICATIVE)
+    }
+
+    /// R*, L*, W* and Y*.
+    pub fn is_liquid(&self) -> bool {
+        self.has_flag(flag::LIQUID)
+    }
+
+    /// M*, N* and NX.
+    pub fn is_nasal(&self) -> bool {
+        self.has_flag(flag::NASAL)
+    }
+
+    /// Articulated with the tongue against the alveolar ridge, such as N*, S* and T*.
+    pub fn is_alveolar(&self) -> bool {
+        self.has_flag(flag::ALVEOLAR)
+    }
+
+    /// The default length of the phoneme in frames, as (unstressed, stressed).
+    ///
+    /// The reference implementation has no lengths for UN, which the parser always replaces with
+    /// AX N*, so this returns `None` for it.
+    pub fn default_lengths(&self) -> Option<(u8, u8)> {
+        PHONEME_LENGTH_TABLE.get(self.index()).copied()
+    }
+
+    /// Whether the phoneme is one of the internal states that the parser inserts.
+    pub fn is_internal(&self) -> bool {
+        self.name() == "**"
+    }
+
+    /// The two release states that follow a plosive, or `None` if the phoneme is not a plosive.
+    pub fn plosive_release(&self) -> Option<(PhonemeKind, PhonemeKind)> {
+        match self {
+            PhonemeKind::B => Some((PhonemeKind::BRelease1, PhonemeKind::BRelease2)),
+            PhonemeKind::D => Some((PhonemeKind::DRelease1, PhonemeKind::DRelease2)),
+            PhonemeKind::G => Some((PhonemeKind::GRelease1, PhonemeKind::GRelease2)),
+            PhonemeKind::Gx => Some((PhonemeKind::GxRelease1, PhonemeKind::GxRelease2)),
+            PhonemeKind::P => Some((PhonemeKind::PRelease1, PhonemeKind::PRelease2)),
+            PhonemeKind::T => Some((PhonemeKind::TRelease1, PhonemeKind::TRelease2)),
+            PhonemeKind::K => Some((PhonemeKind::KRelease1, PhonemeKind::KRelease2)),
+            PhonemeKind::Kx => Some((PhonemeKind::KxRelease1, PhonemeKind::KxRelease2)),
+            _ => None
+        }
+    }
+
+    /// The phoneme that an internal release state belongs to, such as P* for both release states
+    /// of P* and CH for the release of CH. Other phonemes return themselves.
+    pub fn base(&self) -> PhonemeKind {
+        match self {
+            PhonemeKind::ChRelease => PhonemeKind::Ch,
+            PhonemeKind::JRelease => PhonemeKind::J,
+            kind => *PhonemeKind::ALL.iter()
+                .find(|base| base.plosive_release().is_some_and(|(first, second)| first == *kind || second == *kind))
+                .unwrap_or(kind)
+        }
+    }
+
+    /// The voiced counterpart of an unvoiced plosive, which replaces it after an S* (as in SPY).
+    /// Note that UM and UN are flagged as unvoiced plosives as well, but the parser replaces them
+    /// before this rule is applied.
+    pub fn voiced_plosive(&self) -> Option<PhonemeKind> {
+        match self {
+            PhonemeKind::P => Some(PhonemeKind::B),
+            PhonemeKind::T => Some(PhonemeKind::D),
+            PhonemeKind::K => Some(PhonemeKind::G),
+            PhonemeKind::Kx => Some(PhonemeKind::Gx),
+            _ => None
+        }
+    }
+}
+
+impl std::fmt::Display for PhonemeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl std::str::FromStr for PhonemeKind {
+    type Err = ParseError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        PhonemeKind::from_name(name).ok_or_else(|| ParseError::UnknownPhoneme {
+            position: 0,
+            text: name.to_owned()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_round_trip() {
+        for (index, kind) in PhonemeKind::ALL.iter().enumerate() {
+            assert_eq!(PhonemeKind::from_index(kind.index()), Some(*kind));
+
+            // Only positions 46 and 47 are skipped
+            assert_eq!(kind.index(), if index < 46 { index } else { index + 2 });
+        }
+
+        assert_eq!(PhonemeKind::from_index(46), None);
+        assert_eq!(PhonemeKind::from_index(81), None);
+    }
+
+    #[test]
+    fn names() {
+        for kind in PhonemeKind::ALL {
+            let (first, second) = PHONEME_NAME_TABLE[kind.index()];
+            assert_eq!(kind.name(), format!("{}{}", first, second));
+
+            if !kind.is_internal() {
+                assert_eq!(kind.name().parse::<PhonemeKind>().unwrap(), *kind);
+            }
+        }
+
+        assert_eq!(PhonemeKind::from_name("R"), Some(PhonemeKind::R));
+        assert_eq!(PhonemeKind::from_name("/H"), Some(PhonemeKind::SlashH));
+        assert_eq!(PhonemeKind::from_name("**"), None);
+        assert_eq!(PhonemeKind::from_name("IYX"), None);
+        assert_eq!(PhonemeKind::from_name(""), None);
+    }
+
+    #[test]
+    fn articulation() {
+        assert_eq!(PhonemeKind::Pause.articulation(), Articulation::Pause);
+        assert_eq!(PhonemeKind::QuestionMark.articulation(), Articulation::Pause);
+        assert_eq!(PhonemeKind::Iy.articulation(), Articulation::Vowel);
+        assert_eq!(PhonemeKind::Un.articulation(), Articulation::Vowel);
+        assert_eq!(PhonemeKind::Ow.articulation(), Articulation::Diphthong);
+        assert_eq!(PhonemeKind::R.articulation(), Articulation::Liquid);
+        assert_eq!(PhonemeKind::Nx.articulation(), Articulation::Nasal);
+        assert_eq!(PhonemeKind::B.articulation(), Articulation::Plosive);
+        assert_eq!(PhonemeKind::KxRelease2.articulation(), Articulation::Plosive);
+        assert_eq!(PhonemeKind::Ch.articulation(), Articulation::Affricate);
+        assert_eq!(PhonemeKind::J.articulation(), Articulation::Affricate);
+        assert_eq!(PhonemeKind::ChRelease.articulation(), Articulation::Fricative);
+        assert_eq!(PhonemeKind::Dx.articulation(), Articulation::Flap);
+        assert_eq!(PhonemeKind::Q.articulation(), Articulation::GlottalStop);
+        assert_eq!(PhonemeKind::Zh.articulation(), Articulation::Fricative);
+        assert_eq!(PhonemeKind::SlashH.articulation(), Articulation::Aspirate);
+        assert_eq!(PhonemeKind::Wh.articulation(), Articulation::Aspirate);
+    }
+
+    #[test]
+    fn features() {
+        assert!(PhonemeKind::Z.is_voiced() && PhonemeKind::Z.is_alveolar());
+        assert!(!PhonemeKind::S.is_voiced());
+        assert!(PhonemeKind::Ay.is_front_diphthong() && !PhonemeKind::Aw.is_front_diphthong());
+        assert!(PhonemeKind::M.is_stop() && PhonemeKind::Q.is_stop() && !PhonemeKind::F.is_stop());
+        assert!(PhonemeKind::Period.is_punctuation() && PhonemeKind::Period.is_glottal_closure());
+        assert!(!PhonemeKind::Pause.is_punctuation() && PhonemeKind::Pause.is_pause());
+
+        // No phoneme uses the unused flag
+        for kind in PhonemeKind::ALL {
+            assert_eq!(kind.flags() & flag::_UNUSED, 0);
+        }
+    }
+
+    #[test]
+    fn releases() {
+        for kind in PhonemeKind::ALL {
+            if let Some((first, second)) = kind.plosive_release() {
+                assert_eq!(first.index(), kind.index() + 1);
+                assert_eq!(second.index(), kind.index() + 2);
+                assert_eq!(first.base(), *kind);
+                assert_eq!(second.base(), *kind);
+            }
+
+            // Only the internal states belong to another phoneme
+            assert_eq!(kind.base() != *kind, kind.is_internal());
+
+            if let Some(voiced) = kind.voiced_plosive() {
+                assert_eq!(voiced.index(), kind.index() - 12);
+            }
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::parser::{self, ParseError};
+
+#[derive(Debug)]
+pub enum DictionaryError {
+    IO(std::io::Error),
+    InvalidWord {
+        line: usize,
+        word: String
+    },
+    MissingPhonemes {
+        line: usize
+    },
+    InvalidPhonemes {
+        line: usize,
+        err: ParseError
+    }
+}
+
+impl std::error::Error for DictionaryError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DictionaryError::IO(err) => Some(err),
+            DictionaryError::InvalidPhonemes { err, .. } => Some(err),
+            _ => None
+        }
+    }
+}
+
+impl std::fmt::Display for DictionaryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DictionaryError::IO(err) => write!(f, "Could not read dictionary ({})", err),
+            DictionaryError::InvalidWord { line, word } => write!(f, "Invalid word {:?} on line {}, words consist of letters and apostrophes", word, line),
+            DictionaryError::MissingPhonemes { line } => write!(f, "Missing phonemes on line {}", line),
+            DictionaryError::InvalidPhonemes { line, err } => write!(f, "Invalid phonemes on line {} ({})", line, err)
+        }
+    }
+}
+
+impl From<std::io::Error> for DictionaryError {
+    fn from(err: std::io::Error) -> Self {
+        DictionaryError::IO(err)
+    }
+}
+
+/// Pronunciations of whole words that take precedence over the reciter rules.
+///
+/// A dictionary file has one word per line, followed by its pronunciation in SAM's phonetic
+/// notation. Blank lines and lines starting with "#" are ignored:
+///
+/// ```text
+/// # Product names
+/// RUSTSAM   RAH4STSAEM
+/// GIF       JIH4F
+/// ```
+///
+/// Words are matched case insensitively and only as a whole, so the word must not be preceded or
+/// followed by a letter or apostrophe.
+#[derive(Clone, Debug, Default)]
+pub struct Dictionary {
+    words: HashMap<String, String>
+}
+
+impl Dictionary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read a dictionary file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, DictionaryError> {
+        std::fs::read_to_string(path)?.parse()
+    }
+
+    /// Add the pronunciation of a word, replacing any earlier one. The phonemes are not checked.
+    pub fn insert(&mut self, word: &str, phonemes: &str) {
+        self.words.insert(word.to_ascii_uppercase(), phonemes.to_owned());
+    }
+
+    /// The pronunciation of a word.
+    pub fn get(&self, word: &str) -> Option<&str> {
+        self.words.get(&word.to_ascii_uppercase()).map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+}
+
+impl std::str::FromStr for Dictionary {
+    type Err = DictionaryError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut dictionary = Dictionary::new();
+
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (word, phonemes) = line.split_once(char::is_whitespace)
+                .ok_or(DictionaryError::MissingPhonemes { line: line_number })?;
+
+            // The reciter only starts a word at a letter
+            let is_word = word.starts_with(|character: char| character.is_ascii_alphabetic())
+                && word.chars().all(|character| character.is_ascii_alphabetic() || character == '\'');
+
+            if !is_word {
+                return Err(DictionaryError::InvalidWord {
+                    line: line_number,
+                    word: word.to_owned()
+                });
+            }
+
+            let phonemes = phonemes.trim();
+            parser::parse_phonemes(phonemes).map_err(|err| DictionaryError::InvalidPhonemes {
+                line: line_number,
+                err
+            })?;
+
+            dictionary.insert(word, phonemes);
+        }
+
+        Ok(dictionary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let dictionary: Dictionary = "# Comment\n\nRustSAM   RAH4STSAEM\nO'Neil OWNIY4L\n".parse().unwrap();
+
+        assert_eq!(dictionary.len(), 2);
+        assert_eq!(dictionary.get("rustsam"), Some("RAH4STSAEM"));
+        assert_eq!(dictionary.get("O'NEIL"), Some("OWNIY4L"));
+
+        assert!(matches!("RUSTSAM".parse::<Dictionary>(), Err(DictionaryError::MissingPhonemes { line: 1 })));
+        assert!(matches!("\nR2D2 AA".parse::<Dictionary>(), Err(DictionaryError::InvalidWord { line: 2, .. })));
+        assert!(matches!("WORD XYZ".parse::<Dictionary>(), Err(DictionaryError::InvalidPhonemes { line: 1, .. })));
+    }
+}
@@ -0,0 +1,106 @@
+use std::ops::Range;
+
+use super::ReciterError;
+
+/// The part of a rule that did not match the text.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Mismatch {
+    /// The text does not continue with the source of the rule.
+    Source,
+
+    /// The given character of the prefix did not match. The prefix is checked from right to left.
+    Prefix(char),
+
+    /// The given character of the suffix did not match. The suffix is checked from left to right.
+    Suffix(char)
+}
+
+impl std::fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Mismatch::Source => write!(f, "source differs"),
+            Mismatch::Prefix(character) => write!(f, "prefix {:?} failed", character),
+            Mismatch::Suffix(character) => write!(f, "suffix {:?} failed", character)
+        }
+    }
+}
+
+/// A rule that was tried at a position, in the notation of the rule tables.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Attempt {
+    pub rule: String,
+    pub phonemes: String,
+    pub result: Result<(), Mismatch>
+}
+
+/// What produced the phonemes of a step.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Applied {
+    /// A period that is not part of a number.
+    Period,
+
+    /// A character without rules, which is replaced by a space.
+    Space,
+
+    /// A word from the dictionary.
+    Dictionary,
+
+    /// The last of the attempted rules.
+    Rule
+}
+
+/// A single step of the reciter, which consumes part of the text.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Step {
+    /// The byte range of the text. The text is padded with a space at both ends, which occupy
+    /// an empty range at its start and end.
+    pub text: Range<usize>,
+
+    /// The phonemes produced by the step.
+    pub phonemes: String,
+
+    /// What produced the phonemes, or `None` if the step failed.
+    pub applied: Option<Applied>,
+
+    /// The rules that were tried in order, with the reason each of them failed.
+    pub attempts: Vec<Attempt>
+}
+
+/// A trace of the reciter converting a text, see [`Reciter::explain`](super::Reciter::explain).
+#[derive(Debug)]
+pub struct Explanation {
+    /// The text the reciter converted, after normalization if it is enabled.
+    pub text: String,
+
+    pub steps: Vec<Step>,
+
+    /// The error that stopped the conversion. The last step shows the rules that were tried.
+    pub error: Option<ReciterError>
+}
+
+impl std::fmt::Display for Explanation {
+    /// Write each step with the rules it tried. The error is not included.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for step in &self.steps {
+            write!(f, "{:?} -> {:?}", &self.text[step.text.clone()], step.phonemes)?;
+
+            match step.applied {
+                Some(Applied::Period) => writeln!(f, " (period)")?,
+                Some(Applied::Space) => writeln!(f, " (no rules)")?,
+                Some(Applied::Dictionary) => writeln!(f, " (dictionary)")?,
+                Some(Applied::Rule) | None => writeln!(f)?
+            }
+
+            for attempt in &step.attempts {
+                let rule = format!("{:?} -> {:?}", attempt.rule, attempt.phonemes);
+
+                match attempt.result {
+                    Ok(()) => writeln!(f, "    {:32} matched", rule)?,
+                    Err(mismatch) => writeln!(f, "    {:32} {}", rule, mismatch)?
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
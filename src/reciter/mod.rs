@@ -1,8 +1,22 @@
-use std::collections::HashMap;
-use once_cell::sync::Lazy;
+use std::ops::Range;
+use std::sync::Arc;
 
+use crate::Compatibility;
+use crate::parser::ParseError;
+
+use normalize::Normalized;
+
+mod dictionary;
+mod explain;
+mod normalize;
+mod rule_set;
 mod rules;
 
+pub use dictionary::{Dictionary, DictionaryError};
+pub use explain::{Applied, Attempt, Explanation, Mismatch, Step};
+pub use normalize::normalize;
+pub use rule_set::{RuleSet, RuleSetError};
+
 #[derive(Debug)]
 pub enum ReciterError {
     BadPunctuation,
@@ -10,7 +24,11 @@ pub enum ReciterError {
     MissingCloseParenthesis,
     NoRulesForCharacter(char),
     NoMatchingRuleFoundAtIndex(usize),
-    NoMatchingCharacterRuleFoundAtIndex(usize)
+    NoMatchingCharacterRuleFoundAtIndex(usize),
+    EmptySource,
+    InvalidSourceCharacter(char),
+    InvalidContextCharacter(char),
+    InvalidPhonemes(ParseError)
 }
 
 impl std::error::Error for ReciterError {}
@@ -23,7 +41,11 @@ impl std::fmt::Display for ReciterError {
             ReciterError::MissingCloseParenthesis => write!(f, "Missing close parenthesis"),
             ReciterError::NoRulesForCharacter(character) => write!(f, "No rules found for character {:?}", character),
             ReciterError::NoMatchingRuleFoundAtIndex(index) => write!(f, "No matching rule found at index {}", index),
-            ReciterError::NoMatchingCharacterRuleFoundAtIndex(index) => write!(f, "No matching character rule found at index {}", index)
+            ReciterError::NoMatchingCharacterRuleFoundAtIndex(index) => write!(f, "No matching character rule found at index {}", index),
+            ReciterError::EmptySource => write!(f, "Empty source between parentheses"),
+            ReciterError::InvalidSourceCharacter(character) => write!(f, "Invalid rule source character {:?}", character),
+            ReciterError::InvalidContextCharacter(character) => write!(f, "Invalid rule context character {:?}", character),
+            ReciterError::InvalidPhonemes(err) => write!(f, "Invalid phonemes ({})", err)
         }
     }
 }
@@ -108,56 +130,56 @@ fn flags_for_character(character: char) -> CharacterFlag {
     }
 }
 
-static CHARACTER_RULES: Lazy<Vec<ReciterRule>> = Lazy::new(||
-    rules::CHARACTER_RULES.iter().map(|(pattern, replacement)|
-        ReciterRule::new(pattern, replacement).unwrap_or_else(|err| {
-            panic!("Could not instantiate reciter rule for {:?} -> {:?} ({:?})", pattern, replacement, err)
-        })
-    ).collect()
-);
-
-static RULES: Lazy<HashMap<char, Vec<ReciterRule>>> = Lazy::new(|| {
-    let mut rules_per_character: HashMap<char, Vec<ReciterRule>> = HashMap::new();
-
-    for (pattern, replacement) in rules::RULES {
-        let rule = ReciterRule::new(pattern, replacement).unwrap_or_else(|err| {
-            panic!("Could not instantiate reciter rule for {:?} -> {:?} ({:?})", pattern, replacement, err)
-        });
-
-        rules_per_character.entry(rule.source[0]).or_default().push(rule);
-    }
-
-    rules_per_character
-});
-
-struct ReciterRule<'a> {
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct ReciterRule {
     prefix: Vec<char>,
     source: Vec<char>,
     suffix: Vec<char>,
-    target: &'a str
+    target: String
 }
 
-impl<'a> ReciterRule<'a> {
-    fn new(pattern: &'a str, replacement: &'a str) -> Result<Self, ReciterError> {
+impl ReciterRule {
+    fn new(pattern: &str, replacement: &str) -> Result<Self, ReciterError> {
         let (prefix, rest) = pattern.split_once('(').ok_or(ReciterError::MissingOpenParenthesis)?;
         let (source, suffix) = rest.split_once(')').ok_or(ReciterError::MissingCloseParenthesis)?;
 
+        if source.is_empty() {
+            return Err(ReciterError::EmptySource);
+        }
+
+        // The source is matched against uppercase text, where only the characters that have rules
+        // can appear
+        if let Some(character) = source.chars().find(|character| !has_flags(*character, flag::ALPHA_OR_QUOTE | flag::RULESET_2)) {
+            return Err(ReciterError::InvalidSourceCharacter(character));
+        }
+
+        // Context characters are either matched literally or one of the character classes
+        let is_literal = |character: char| has_flags(character, flag::ALPHA_OR_QUOTE);
+
+        if let Some(character) = prefix.chars().find(|character| !is_literal(*character) && !" #.&@^+:".contains(*character)) {
+            return Err(ReciterError::InvalidContextCharacter(character));
+        }
+
+        if let Some(character) = suffix.chars().find(|character| !is_literal(*character) && !" #.&@^+:%".contains(*character)) {
+            return Err(ReciterError::InvalidContextCharacter(character));
+        }
+
         Ok(Self {
             prefix: prefix.chars().collect(),
             source: source.chars().collect(),
             suffix: suffix.chars().collect(),
-            target: replacement
+            target: replacement.to_owned()
         })
     }
 
-    fn check_prefix(&self, text: &[char], position: usize) -> bool {
+    fn check_prefix(&self, text: &[char], position: usize, compatibility: Compatibility) -> Result<(), Mismatch> {
         let mut position = position;
 
         for rule_character in self.prefix.iter().rev() {
             if has_flags(*rule_character, flag::ALPHA_OR_QUOTE) {
                 if position == 0 || text[position - 1] != *rule_character {
                     // Rule char does not match.
-                    return false;
+                    return Err(Mismatch::Prefix(*rule_character));
                 }
 
                 position -= 1;
@@ -170,7 +192,7 @@ impl<'a> ReciterRule<'a> {
                     if position >= 1 && !has_flags_at(text, position - 1, flag::ALPHA_OR_QUOTE) {
                         position -= 1;
                     } else {
-                        return false;
+                        return Err(Mismatch::Prefix(*rule_character));
                     }
                 },
 
@@ -179,7 +201,7 @@ impl<'a> ReciterRule<'a> {
                     if position >= 1 && has_flags_at(text, position - 1, flag::VOWEL_OR_Y) {
                         position -= 1;
                     } else {
-                        return false;
+                        return Err(Mismatch::Prefix(*rule_character));
                     }
                 },
 
@@ -190,7 +212,7 @@ impl<'a> ReciterRule<'a> {
                     if position >= 1 && has_flags_at(text, position - 1, flag::OXO8) {
                         position -= 1;
                     } else {
-                        return false;
+                        return Err(Mismatch::Prefix(*rule_character));
                     }
                 },
 
@@ -207,7 +229,7 @@ impl<'a> ReciterRule<'a> {
                         // SH
                         position -= 2;
                     } else {
-                        return false;
+                        return Err(Mismatch::Prefix(*rule_character));
                     }
                 },
 
@@ -216,11 +238,16 @@ impl<'a> ReciterRule<'a> {
                     if position >= 1 && has_flags_at(text, position - 1, flag::VOICED) {
                         // Voiced
                         position -= 1;
+                    } else if compatibility == Compatibility::Corrected && position >= 2 && text[position - 1] == 'H' && matches!(text[position - 2], 'C' | 'S' | 'T') {
+                        // The intended set is one of T S R D L Z N J TH CH SH. The reference
+                        // implementation compares the 'H' itself against 'C', 'S' and 'T' instead
+                        // of the character before it.
+                        position -= 2;
                     } else {
                         // Note: logic errors in the original implementation make the "H" check
                         // irrelevant. There is also a check for "C", "S", or "T", that is
                         // never true.
-                        return false;
+                        return Err(Mismatch::Prefix(*rule_character));
                     }
                 },
 
@@ -229,7 +256,7 @@ impl<'a> ReciterRule<'a> {
                     if position >= 1 && has_flags_at(text, position - 1, flag::CONSONANT) {
                         position -= 1;
                     } else {
-                        return false;
+                        return Err(Mismatch::Prefix(*rule_character));
                     }
                 },
 
@@ -238,7 +265,7 @@ impl<'a> ReciterRule<'a> {
                     if position >= 1 && (text[position - 1] == 'E' || text[position - 1] == 'I' || text[position - 1] == 'Y') {
                         position -= 1;
                     } else {
-                        return false;
+                        return Err(Mismatch::Prefix(*rule_character));
                     }
                 },
 
@@ -249,21 +276,21 @@ impl<'a> ReciterRule<'a> {
                     }
                 },
 
-                _ => panic!("Unrecognized rule prefix character {:?}", rule_character)
+                _ => unreachable!("Unrecognized rule prefix character {:?}", rule_character)
             };
         }
 
-        true
+        Ok(())
     }
 
-    fn check_suffix(&self, text: &[char], position: usize) -> bool {
+    fn check_suffix(&self, text: &[char], position: usize, compatibility: Compatibility) -> Result<(), Mismatch> {
         let mut position = position;
 
         for rule_character in &self.suffix {
             // do we have to handle the byte specially?
             if has_flags(*rule_character, flag::ALPHA_OR_QUOTE) {
                 if position + 1 >= text.len() || text[position + 1] != *rule_character {
-                    return false;
+                    return Err(Mismatch::Suffix(*rule_character));
                 }
 
                 position += 1;
@@ -277,7 +304,7 @@ impl<'a> ReciterRule<'a> {
                     if position + 1 < text.len() && !has_flags_at(text, position + 1, flag::ALPHA_OR_QUOTE) {
                         position += 1;
                     } else {
-                        return false;
+                        return Err(Mismatch::Suffix(*rule_character));
                     }
                 },
 
@@ -286,7 +313,7 @@ impl<'a> ReciterRule<'a> {
                     if position + 1 < text.len() && has_flags_at(text, position + 1, flag::VOWEL_OR_Y) {
                         position += 1;
                     } else {
-                        return false;
+                        return Err(Mismatch::Suffix(*rule_character));
                     }
                 },
 
@@ -297,7 +324,7 @@ impl<'a> ReciterRule<'a> {
                     if position + 1 < text.len() && has_flags_at(text, position, flag::OXO8) {
                         position += 1;
                     } else {
-                        return false;
+                        return Err(Mismatch::Suffix(*rule_character));
                     }
                 },
 
@@ -314,7 +341,7 @@ impl<'a> ReciterRule<'a> {
                         // HS
                         position += 2;
                     } else {
-                        return false;
+                        return Err(Mismatch::Suffix(*rule_character));
                     }
                 },
 
@@ -326,15 +353,22 @@ impl<'a> ReciterRule<'a> {
                     if position + 1 < text.len() && has_flags_at(text, position + 1, flag::VOICED) {
                         // Voiced character
                         position += 1;
+                    } else if compatibility == Compatibility::Corrected {
+                        // One of T S R D L Z N J TH CH SH, as in the prefix. All of these but CH
+                        // start with a voiced character.
+                        if position + 2 < text.len() && text[position + 1] == 'C' && text[position + 2] == 'H' {
+                            position += 2;
+                        } else {
+                            return Err(Mismatch::Suffix(*rule_character));
+                        }
                     } else if position + 1 < text.len() && text[position + 1] == 'H' {
                         // H
                         position += 1;
-                    } else if position + 1 < text.len() && (text[position + 1] == 'C' || text[position + 1] == 'S' || text[position + 1] == 'T') {
-                        // C, S, or T
-                        // FIXME: This is illogical and can never be reached. Bug in original code? reciter.c:489 (pos37367)
-                        unreachable!("Should not be reachable for input character {:?}, bug in original code?", text[position + 1]);
                     } else {
-                        return false;
+                        // The original code goes on to accept C, S or T here (reciter.c:489,
+                        // pos37367), but S and T are voiced and handled above. A C is treated as
+                        // a mismatch.
+                        return Err(Mismatch::Suffix(*rule_character));
                     }
                 },
 
@@ -343,7 +377,7 @@ impl<'a> ReciterRule<'a> {
                     if position + 1 < text.len() && has_flags_at(text, position + 1, flag::CONSONANT) {
                         position += 1;
                     } else {
-                        return false;
+                        return Err(Mismatch::Suffix(*rule_character));
                     }
                 },
 
@@ -352,7 +386,7 @@ impl<'a> ReciterRule<'a> {
                     if position + 1 < text.len() && (text[position + 1] == 'E' || text[position + 1] == 'I' || text[position + 1] == 'Y') {
                         position += 1;
                     } else {
-                        return false;
+                        return Err(Mismatch::Suffix(*rule_character));
                     }
                 },
 
@@ -387,34 +421,39 @@ impl<'a> ReciterRule<'a> {
                         // EFUL
                         position += 4;
                     } else {
-                        return false;
+                        return Err(Mismatch::Suffix(*rule_character));
                     }
                 },
 
-                _ => panic!("Unrecognized rule suffix character {:?}", rule_character)
+                _ => unreachable!("Unrecognized rule suffix character {:?}", rule_character)
             };
         }
 
-        true
+        Ok(())
     }
 
-    fn matches(&self, text: &[char], position: usize) -> bool {
+    fn matches(&self, text: &[char], position: usize, compatibility: Compatibility) -> Result<(), Mismatch> {
         // Check if the source matches
         if !text[position..].starts_with(&self.source) {
-            return false;
+            return Err(Mismatch::Source);
         }
 
         // Check if the prefix matches
-        if !self.check_prefix(text, position) {
-            return false;
-        }
+        self.check_prefix(text, position, compatibility)?;
 
         // Check if the suffix matches
-        if !self.check_suffix(text, position + (self.source.len() - 1)) {
-            return false;
-        }
+        self.check_suffix(text, position + (self.source.len() - 1), compatibility)
+    }
+}
 
-        true
+impl std::fmt::Display for ReciterRule {
+    /// The rule in the notation of the rule tables, such as " (THE)#".
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let prefix: String = self.prefix.iter().collect();
+        let source: String = self.source.iter().collect();
+        let suffix: String = self.suffix.iter().collect();
+
+        write!(f, "{}({}){}", prefix, source, suffix)
     }
 }
 
@@ -426,44 +465,179 @@ fn has_flags_at(text: &[char], position: usize, flag: CharacterFlag) -> bool {
     flags_for_character(text[position]) & flag != 0
 }
 
-/// Convert the input text to a representation using phonemes.
-pub fn text_to_phonemes(text: &str) -> Result<String, ReciterError> {
-    let mut output = String::new();
+/// Converts English text to SAM's phonetic notation using the rules of the original reciter.
+#[derive(Clone, Debug)]
+pub struct Reciter {
+    compatibility: Compatibility,
+    dictionary: Dictionary,
+    rules: Arc<RuleSet>,
+    normalize: bool
+}
+
+impl Reciter {
+    /// Create a reciter with the English rules of the original.
+    pub fn new() -> Self {
+        Self {
+            compatibility: Compatibility::default(),
+            dictionary: Dictionary::new(),
+            rules: RuleSet::shared_english(),
+            normalize: false
+        }
+    }
 
-    // Pad the input string with spaces so the ends have word boundaries
-    let input: Vec<char> = std::iter::once(' ').chain(text.to_ascii_uppercase().chars()).chain(std::iter::once(' ')).collect();
+    /// Use a different set of rules, for example one loaded from a file with [`RuleSet::load`].
+    pub fn with_rules(mut self, rules: RuleSet) -> Self {
+        self.rules = Arc::new(rules);
+        self
+    }
+
+    /// Whether to reproduce the bugs of the reference implementation. Corrected mode matches '@'
+    /// in rule contexts against the digraphs TH, CH and SH as the rules intend.
+    pub fn with_compatibility(mut self, compatibility: Compatibility) -> Self {
+        self.compatibility = compatibility;
+        self
+    }
+
+    /// Pronounce the words in the dictionary as listed instead of applying the rules to them.
+    pub fn with_dictionary(mut self, dictionary: Dictionary) -> Self {
+        self.dictionary = dictionary;
+        self
+    }
+
+    /// Whether to read numbers, amounts of money and times as words instead of digit by digit,
+    /// see [`normalize`]. The number words are pronounced with corrected phonemes where the rules
+    /// get them wrong, unless the dictionary lists them.
+    pub fn with_normalization(mut self, normalize: bool) -> Self {
+        self.normalize = normalize;
+        self
+    }
+
+    /// Convert the input text to a representation using phonemes.
+    pub fn text_to_phonemes(&self, text: &str) -> Result<String, ReciterError> {
+        Ok(self.align(text)?.phonemes)
+    }
+
+    /// Convert the input text to phonemes like [`Reciter::text_to_phonemes`], recording which part
+    /// of the text produced which part of the phonemes.
+    ///
+    /// With normalization the segments refer to the original text, and all the words of a
+    /// number belong to the segment that starts with them.
+    pub fn align(&self, text: &str) -> Result<Alignment, ReciterError> {
+        if !self.normalize {
+            return self.recite(text, None);
+        }
+
+        let normalized = Normalized::new(text);
+        let mut alignment = self.recite(&normalized.text, None)?;
+
+        for segment in &mut alignment.segments {
+            segment.text = normalized.original_range(segment.text.clone());
+        }
+
+        Ok(alignment)
+    }
 
-    let mut index = 0;
+    /// Convert the input text to phonemes, recording the rules that were tried at each step and
+    /// why they did or did not match. A failed conversion is traced up to the failing step.
+    ///
+    /// With normalization the trace refers to the normalized text.
+    pub fn explain(&self, text: &str) -> Explanation {
+        let text = match self.normalize {
+            true => normalize(text),
+            false => text.to_owned()
+        };
+
+        let mut steps = Vec::new();
+        let error = self.recite(&text, Some(&mut steps)).err();
+
+        Explanation {
+            text,
+            steps,
+            error
+        }
+    }
+
+    fn recite(&self, text: &str, mut steps: Option<&mut Vec<Step>>) -> Result<Alignment, ReciterError> {
+        let mut output = String::new();
+        let mut segments = Vec::new();
+
+        // Pad the input string with spaces so the ends have word boundaries
+        let input: Vec<char> = std::iter::once(' ').chain(text.to_ascii_uppercase().chars()).chain(std::iter::once(' ')).collect();
+
+        // The byte offset of every character of the text, and of its end
+        let offsets: Vec<usize> = text.char_indices().map(|(offset, _)| offset).chain(std::iter::once(text.len())).collect();
+        let offset = |index: usize| offsets[index.clamp(1, offsets.len()) - 1];
+
+        let mut index = 0;
+
+        // Note: the original implementation bounds this by a maximum of 10000 iterations due to a
+        // lack of error checking.
+        while index < input.len() {
+            let mut attempts = steps.as_ref().map(|_| Vec::new());
+            let result = self.next_rule(&input, index, attempts.as_mut());
+
+            if let Some(steps) = steps.as_mut() {
+                let (length, target, applied) = match &result {
+                    Ok((length, target, applied)) => (*length, *target, Some(applied.clone())),
+                    Err(_) => (1, "", None)
+                };
+
+                steps.push(Step {
+                    text: offset(index)..offset(index + length),
+                    phonemes: target.to_owned(),
+                    applied,
+                    attempts: attempts.unwrap_or_default()
+                });
+            }
+
+            let (length, target, _) = result?;
+
+            segments.push(Segment {
+                text: offset(index)..offset(index + length),
+                phonemes: output.len()..output.len() + target.len()
+            });
+
+            output += target;
+            index += length;
+        }
+
+        // Drop the output of the trailing padding
+        output.pop();
+
+        for segment in &mut segments {
+            segment.phonemes.start = segment.phonemes.start.min(output.len());
+            segment.phonemes.end = segment.phonemes.end.min(output.len());
+        }
+
+        if let Some(step) = steps.and_then(|steps| steps.last_mut()) {
+            step.phonemes.pop();
+        }
+
+        Ok(Alignment {
+            phonemes: output,
+            segments
+        })
+    }
 
-    // Note: the original implementation bounds this by a maximum of 10000 iterations due to a lack
-    // of error checking.
-    while index < input.len() {
+    // The number of input characters consumed at the index, the phonemes they produce and where
+    // the phonemes came from. The rules that were tried are added to the attempts.
+    fn next_rule(&self, input: &[char], index: usize, attempts: Option<&mut Vec<Attempt>>) -> Result<(usize, &str, Applied), ReciterError> {
         let character = input[index];
 
         // Check for "." not followed by a number
-        if character == '.' && (index + 1 >= input.len() || !has_flags_at(&input, index + 1, flag::NUMERIC)) {
-            output += ".";
-            index += 1;
-            continue;
+        if character == '.' && (index + 1 >= input.len() || !has_flags_at(input, index + 1, flag::NUMERIC)) {
+            return Ok((1, ".", Applied::Period));
         }
 
         // Replace characters without flags with spaces
         if flags_for_character(character) == 0 {
-            output += " ";
-            index += 1;
-            continue;
+            return Ok((1, " ", Applied::Space));
         }
 
         // Apply character rules if the rule set 2 flag is set
         if has_flags(character, flag::RULESET_2) {
-            if let Some(rule) = CHARACTER_RULES.iter().find(|rule| rule.matches(&input, index)) {
-                index += rule.source.len();
-                output += rule.target;
-            } else {
-                return Err(ReciterError::NoMatchingCharacterRuleFoundAtIndex(index));
-            }
-
-            continue;
+            return self.find_rule(input, index, attempts)
+                .ok_or(ReciterError::NoMatchingCharacterRuleFoundAtIndex(index));
         }
 
         // Non-alpha or quote characters should be covered by rule set 2
@@ -471,26 +645,106 @@ pub fn text_to_phonemes(text: &str) -> Result<String, ReciterError> {
             return Err(ReciterError::BadPunctuation);
         }
 
+        if let Some((length, phonemes)) = self.dictionary_word(input, index) {
+            return Ok((length, phonemes, Applied::Dictionary));
+        }
+
         // Find and apply the first matching rule that has the character as its starting character
-        if let Some(rules) = RULES.get(&character) {
-            if let Some(rule) = rules.iter().find(|rule| rule.matches(&input, index)) {
-                index += rule.source.len();
-                output += rule.target;
-            } else {
-                return Err(ReciterError::NoMatchingRuleFoundAtIndex(index));
-            }
-        } else {
+        if self.rules.rules_for(character).is_none() {
             return Err(ReciterError::NoRulesForCharacter(character));
         }
+
+        self.find_rule(input, index, attempts)
+            .ok_or(ReciterError::NoMatchingRuleFoundAtIndex(index))
+    }
+
+    // The length and phonemes of the first rule for the character at the index that matches
+    fn find_rule(&self, input: &[char], index: usize, mut attempts: Option<&mut Vec<Attempt>>) -> Option<(usize, &str, Applied)> {
+        self.rules.rules_for(input[index])?
+            .find(|rule| {
+                let result = rule.matches(input, index, self.compatibility);
+
+                if let Some(attempts) = attempts.as_mut() {
+                    attempts.push(Attempt {
+                        rule: rule.to_string(),
+                        phonemes: rule.target.clone(),
+                        result
+                    });
+                }
+
+                result.is_ok()
+            })
+            .map(|rule| (rule.source.len(), rule.target.as_str(), Applied::Rule))
+    }
+
+    // The length and pronunciation of the word starting at the index, if it is in the dictionary.
+    // Words are delimited like the ' ' in rule contexts, by anything but a letter or apostrophe.
+    fn dictionary_word(&self, input: &[char], index: usize) -> Option<(usize, &str)> {
+        if (self.dictionary.is_empty() && !self.normalize) || (index > 0 && has_flags_at(input, index - 1, flag::ALPHA_OR_QUOTE)) {
+            return None;
+        }
+
+        let length = input[index..].iter()
+            .take_while(|character| has_flags(**character, flag::ALPHA_OR_QUOTE))
+            .count();
+
+        let word: String = input[index..index + length].iter().collect();
+
+        self.dictionary.get(&word)
+            .or_else(|| self.normalize.then(|| normalize::pronunciation(&word)).flatten())
+            .map(|phonemes| (length, phonemes))
     }
+}
+
+/// A part of the text and the phonemes that the reciter produced for it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Segment {
+    /// The byte range of the text.
+    pub text: Range<usize>,
+
+    /// The byte range of the phonemes in [`Alignment::phonemes`]. This is empty for silent text,
+    /// such as the E at the end of MADE.
+    pub phonemes: Range<usize>
+}
+
+/// The phonemes for a text, along with the segment of the text each rule was applied to.
+///
+/// The segments are in order and cover both the text and the phonemes without gaps.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Alignment {
+    pub phonemes: String,
+    pub segments: Vec<Segment>
+}
+
+impl Alignment {
+    /// The byte range of the text that produced the given range of the phonemes, for example to
+    /// find the word a mispronounced phoneme came from. Returns `None` if the range is empty.
+    pub fn text_range(&self, phonemes: Range<usize>) -> Option<Range<usize>> {
+        let mut overlapping = self.segments.iter()
+            .filter(|segment| segment.phonemes.start < phonemes.end && phonemes.start < segment.phonemes.end);
+
+        let first = overlapping.next()?;
+        let last = overlapping.next_back().unwrap_or(first);
 
-    //Ok(output.trim().to_owned())
-    Ok(output[..output.len() - 1].to_owned())
+        Some(first.text.start..last.text.end)
+    }
+}
+
+impl Default for Reciter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Convert the input text to a representation using phonemes.
+pub fn text_to_phonemes(text: &str) -> Result<String, ReciterError> {
+    Reciter::new().text_to_phonemes(text)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::text_to_phonemes;
+    use super::{text_to_phonemes, Applied, Dictionary, Mismatch, Reciter, ReciterError, RuleSet};
+    use crate::Compatibility;
 
     use std::fs::File;
     use std::path::PathBuf;
@@ -503,6 +757,114 @@ mod tests {
         phonemes: String
     }
 
+    #[test]
+    fn digraph_context() {
+        // The '@' in "@(EW)" is meant to match the CH
+        let samjs = Reciter::new().text_to_phonemes("CHEW").unwrap();
+        let corrected = Reciter::new().with_compatibility(Compatibility::Corrected).text_to_phonemes("CHEW").unwrap();
+
+        assert_eq!(samjs, text_to_phonemes("CHEW").unwrap());
+        assert_eq!(samjs, " CHYUW");
+        assert_eq!(corrected, " CHUW");
+    }
+
+    #[test]
+    fn alignment() {
+        let text = "Café, world";
+        let alignment = Reciter::new().align(text).unwrap();
+
+        assert_eq!(alignment.phonemes, text_to_phonemes(text).unwrap());
+
+        // The segments cover the text and the phonemes without gaps
+        let mut text_end = 0;
+        let mut phonemes_end = 0;
+
+        for segment in &alignment.segments {
+            assert_eq!(segment.text.start, text_end);
+            assert_eq!(segment.phonemes.start, phonemes_end);
+            text_end = segment.text.end;
+            phonemes_end = segment.phonemes.end;
+        }
+
+        assert_eq!(text_end, text.len());
+        assert_eq!(phonemes_end, alignment.phonemes.len());
+
+        // The phonemes of the second word map back to it
+        let world = alignment.phonemes.rfind(' ').unwrap() + 1..alignment.phonemes.len();
+        assert_eq!(alignment.text_range(world), Some(7..text.len()));
+        assert_eq!(alignment.text_range(0..0), None);
+    }
+
+    #[test]
+    fn dictionary() {
+        let mut dictionary = Dictionary::new();
+        dictionary.insert("RustSAM", "RAH4STSAEM");
+
+        let reciter = Reciter::new().with_dictionary(dictionary);
+        let alignment = reciter.align("Try rustsam, rustsams").unwrap();
+
+        // Only the whole word is replaced
+        assert!(alignment.phonemes.contains(" RAH4STSAEM,"), "{}", alignment.phonemes);
+        assert_eq!(alignment.phonemes.matches("RAH4STSAEM").count(), 1);
+        assert!(alignment.segments.iter().any(|segment| segment.text == (4..11)));
+
+        assert_eq!(reciter.text_to_phonemes("HELLO").unwrap(), text_to_phonemes("HELLO").unwrap());
+    }
+
+    #[test]
+    fn custom_rules() {
+        let rules: RuleSet = " (C)A -> K\n(A) -> AE\n(T) -> T\n".parse().unwrap();
+        let reciter = Reciter::new().with_rules(rules);
+
+        assert_eq!(reciter.text_to_phonemes("CAT").unwrap(), " KAET");
+        assert!(matches!(reciter.text_to_phonemes("DOG"), Err(ReciterError::NoRulesForCharacter('D'))));
+        assert!(matches!(reciter.text_to_phonemes("TAC"), Err(ReciterError::NoMatchingRuleFoundAtIndex(3))));
+    }
+
+    #[test]
+    fn explain() {
+        let rules: RuleSet = " (C)A -> K\n(A)T -> AE\n(T)# -> T\n".parse().unwrap();
+        let explanation = Reciter::new().with_rules(rules).explain("Cat");
+
+        assert_eq!(explanation.steps.len(), 4);
+        assert_eq!(explanation.steps[0].applied, Some(Applied::Space));
+        assert_eq!(explanation.steps[1].text, 0..1);
+        assert_eq!(explanation.steps[1].phonemes, "K");
+        assert_eq!(explanation.steps[1].applied, Some(Applied::Rule));
+        assert_eq!(explanation.steps[2].attempts[0].rule, "(A)T");
+        assert_eq!(explanation.steps[2].attempts[0].result, Ok(()));
+
+        // The failing step shows why the rule did not match
+        let failed = &explanation.steps[3];
+        assert_eq!(failed.text, 2..3);
+        assert_eq!(failed.applied, None);
+        assert_eq!(failed.attempts[0].result, Err(Mismatch::Suffix('#')));
+        assert!(matches!(explanation.error, Some(ReciterError::NoMatchingRuleFoundAtIndex(3))));
+
+        let explanation = Reciter::new().explain("THE");
+        assert!(explanation.error.is_none());
+        assert_eq!(explanation.steps.iter().map(|step| step.phonemes.as_str()).collect::<String>(), text_to_phonemes("THE").unwrap());
+        assert!(explanation.to_string().contains("matched"));
+    }
+
+    #[test]
+    fn normalization() {
+        let text = "Bonus: $20, 19th";
+        let reciter = Reciter::new().with_normalization(true);
+        let alignment = reciter.align(text).unwrap();
+
+        assert_eq!(alignment.phonemes, " BAHNAHS. TWEHNTIY DAALAA5RZ, NAY5NTIY4NTH");
+        assert_eq!(alignment.segments.last().unwrap().text.end, text.len());
+
+        // The phonemes of the amount map back to the digits
+        let start = alignment.phonemes.find("TWEHNTIY").unwrap();
+        let end = alignment.phonemes.find(',').unwrap();
+        assert_eq!(&text[alignment.text_range(start..end).unwrap()], "$20");
+
+        assert_eq!(reciter.explain(text).text, "Bonus: TWENTY DOLLARS, NINETEENTH");
+        assert_ne!(Reciter::new().text_to_phonemes(text).unwrap(), alignment.phonemes);
+    }
+
     #[test]
     fn sanity() {
         assert_eq!(text_to_phonemes("").unwrap(), "");
@@ -0,0 +1,572 @@
+use std::ops::Range;
+
+const ONES: [&str; 20] = [
+    "ZERO", "ONE", "TWO", "THREE", "FOUR", "FIVE", "SIX", "SEVEN", "EIGHT", "NINE", "TEN",
+    "ELEVEN", "TWELVE", "THIRTEEN", "FOURTEEN", "FIFTEEN", "SIXTEEN", "SEVENTEEN", "EIGHTEEN", "NINETEEN"
+];
+
+const TENS: [&str; 10] = [
+    "", "", "TWENTY", "THIRTY", "FORTY", "FIFTY", "SIXTY", "SEVENTY", "EIGHTY", "NINETY"
+];
+
+const SCALES: [(u64, &str); 4] = [
+    (1_000_000_000_000, "TRILLION"),
+    (1_000_000_000, "BILLION"),
+    (1_000_000, "MILLION"),
+    (1_000, "THOUSAND")
+];
+
+// Numbers from a quadrillion up are read digit by digit
+const LARGEST: u64 = 999_999_999_999_999;
+
+// Number words that the English rules mispronounce
+const PRONUNCIATIONS: [(&str, &str); 16] = [
+    ("ZERO", "ZIY4ROW"),
+    ("SECOND", "SEH4KUND"),
+    ("NINTH", "NAY4NTH"),
+    ("NINETEEN", "NAY5NTIY4N"),
+    ("NINETEENTH", "NAY5NTIY4NTH"),
+    ("NINETY", "NAY4NTIY"),
+    ("NINETIES", "NAY4NTIYZ"),
+    ("SIXES", "SIH4KSIHZ"),
+    ("TWENTIETH", "TWEH4NTIYIHTH"),
+    ("THIRTIETH", "THER4TIYIHTH"),
+    ("FORTIETH", "FAO4RTIYIHTH"),
+    ("FIFTIETH", "FIH4FTIYIHTH"),
+    ("SIXTIETH", "SIH4KSTIYIHTH"),
+    ("SEVENTIETH", "SEH4VEHNTIYIHTH"),
+    ("EIGHTIETH", "EY4TIYIHTH"),
+    ("NINETIETH", "NAY4NTIYIHTH")
+];
+
+/// The pronunciation of an uppercase number word produced by [`normalize`], for the words that
+/// the English rules get wrong.
+pub(super) fn pronunciation(word: &str) -> Option<&'static str> {
+    PRONUNCIATIONS.iter()
+        .find(|(number_word, _)| *number_word == word)
+        .map(|(_, phonemes)| *phonemes)
+}
+
+#[derive(Clone, Copy)]
+enum Currency {
+    Dollar,
+    Pound,
+    Euro
+}
+
+impl Currency {
+    fn from_symbol(symbol: char) -> Option<Self> {
+        match symbol {
+            '$' => Some(Currency::Dollar),
+            '£' => Some(Currency::Pound),
+            '€' => Some(Currency::Euro),
+            _ => None
+        }
+    }
+
+    // The singular and plural of the currency unit
+    fn major(&self) -> (&'static str, &'static str) {
+        match self {
+            Currency::Dollar => ("DOLLAR", "DOLLARS"),
+            Currency::Pound => ("POUND", "POUNDS"),
+            Currency::Euro => ("EURO", "EUROS")
+        }
+    }
+
+    // The singular and plural of a hundredth of the unit
+    fn minor(&self) -> (&'static str, &'static str) {
+        match self {
+            Currency::Pound => ("PENNY", "PENCE"),
+            Currency::Dollar | Currency::Euro => ("CENT", "CENTS")
+        }
+    }
+}
+
+/// A text with its numbers replaced by words, and where each replacement came from.
+pub(super) struct Normalized {
+    pub text: String,
+    replacements: Vec<Replacement>
+}
+
+// The byte ranges of a replaced number in the original and the normalized text
+struct Replacement {
+    original: Range<usize>,
+    normalized: Range<usize>
+}
+
+impl Normalized {
+    pub fn new(text: &str) -> Self {
+        let characters: Vec<char> = text.chars().collect();
+
+        // The byte offset of every character of the text, and of its end
+        let offsets: Vec<usize> = text.char_indices().map(|(offset, _)| offset).chain(std::iter::once(text.len())).collect();
+
+        let mut normalized = Normalized {
+            text: String::with_capacity(text.len()),
+            replacements: Vec::new()
+        };
+
+        let mut copied = 0;
+        let mut index = 0;
+
+        while index < characters.len() {
+            let Some((end, words)) = read_number(&characters, index) else {
+                index += 1;
+                continue;
+            };
+
+            normalized.text += &text[offsets[copied]..offsets[index]];
+
+            let start = normalized.text.len();
+            normalized.text += &words;
+
+            normalized.replacements.push(Replacement {
+                original: offsets[index]..offsets[end],
+                normalized: start..normalized.text.len()
+            });
+
+            index = end;
+            copied = end;
+        }
+
+        normalized.text += &text[offsets[copied]..];
+        normalized
+    }
+
+    /// The byte range of the original text that produced a range of the normalized text. The
+    /// words of a number belong to the part of the range that starts with them, so consecutive
+    /// ranges stay in order without gaps.
+    pub fn original_range(&self, range: Range<usize>) -> Range<usize> {
+        self.original_offset(range.start)..self.original_offset(range.end)
+    }
+
+    fn original_offset(&self, offset: usize) -> usize {
+        let mut previous = (0, 0);
+
+        for replacement in &self.replacements {
+            if offset <= replacement.normalized.start {
+                break;
+            }
+
+            if offset < replacement.normalized.end {
+                return replacement.original.end;
+            }
+
+            previous = (replacement.normalized.end, replacement.original.end);
+        }
+
+        previous.1 + offset - previous.0
+    }
+}
+
+/// Replace numbers in the text with words, so "Score 1,250 at 9:05pm" reads as "Score ONE
+/// THOUSAND TWO HUNDRED FIFTY at NINE OH FIVE PEE EM".
+///
+/// Besides cardinal numbers this expands:
+///
+/// * Ordinals, such as 1st, 22nd and 103rd
+/// * Decimals and negative numbers, such as 3.14 and -5
+/// * Percentages, such as 12.5%
+/// * Amounts of dollars, pounds and euros, such as $4.99, £3, €1.5 million
+/// * Years from 1100 to 2099 and decades, such as 1984 and 1990s. Write 1,984 to read a four digit
+///   number as a cardinal instead.
+/// * Times on the 24 hour clock and with am or pm, such as 14:00, 9:30 and 7pm
+///
+/// Numbers that are part of a word, such as R2D2 or 1.2.3, are left alone and read digit by digit
+/// by the reciter rules.
+pub fn normalize(text: &str) -> String {
+    Normalized::new(text).text
+}
+
+// The end of the number starting at the index and its words, if there is a number
+fn read_number(characters: &[char], start: usize) -> Option<(usize, String)> {
+    let at = |index: usize| characters.get(index).copied().unwrap_or(' ');
+    let digit_at = |index: usize| at(index).is_ascii_digit();
+
+    // Numbers start at a word boundary and not after a separator within a number, as in 1.2.3
+    if start > 0 && (at(start - 1).is_alphanumeric() || (matches!(at(start - 1), '.' | ',' | ':') && start > 1 && digit_at(start - 2))) {
+        return None;
+    }
+
+    let mut index = start;
+
+    let negative = at(index) == '-';
+    if negative {
+        index += 1;
+    }
+
+    let currency = Currency::from_symbol(at(index));
+    if currency.is_some() {
+        index += 1;
+    }
+
+    if !digit_at(index) {
+        return None;
+    }
+
+    let (end, words) = if let (false, None, Some(time)) = (negative, currency, read_time(characters, index)) {
+        time
+    } else {
+        let (integer, grouped, mut index) = read_integer(characters, index);
+
+        let mut fraction = None;
+        if at(index) == '.' && digit_at(index + 1) {
+            let digits = characters[index + 1..].iter().take_while(|character| character.is_ascii_digit()).count();
+            fraction = Some(characters[index + 1..index + 1 + digits].iter().collect::<String>());
+            index += 1 + digits;
+        }
+
+        let plain = !negative && currency.is_none() && fraction.is_none();
+        let suffix = [at(index), at(index + 1)].map(|character| character.to_ascii_lowercase());
+
+        if let Some(currency) = currency {
+            read_amount(characters, index, currency, &integer, fraction.as_deref())
+        } else if plain && matches!(suffix, ['s', 't'] | ['n', 'd'] | ['r', 'd'] | ['t', 'h']) {
+            (index + 2, ordinal(&integer_words(&integer)))
+        } else if plain && suffix[0] == 's' {
+            (index + 1, plural(&year_or_integer_words(&integer, grouped)))
+        } else if at(index) == '%' {
+            (index + 1, decimal_words(&integer, fraction.as_deref()) + " PERCENT")
+        } else if let Some((end, meridiem)) = read_meridiem(characters, index).filter(|_| plain && matches!(integer.parse::<u8>(), Ok(1..=12))) {
+            // Only hours are followed by am or pm
+            (end, format!("{} {}", integer_words(&integer), meridiem))
+        } else if plain {
+            (index, year_or_integer_words(&integer, grouped))
+        } else {
+            (index, decimal_words(&integer, fraction.as_deref()))
+        }
+    };
+
+    // The number must not run into a word, or continue as something like a version number
+    if at(end).is_alphanumeric() || (matches!(at(end), '.' | ',' | ':') && digit_at(end + 1)) {
+        return None;
+    }
+
+    match negative {
+        true => Some((end, format!("MINUS {}", words))),
+        false => Some((end, words))
+    }
+}
+
+// The digits of the integer at the index without grouping commas, whether it had any, and the
+// index after it
+fn read_integer(characters: &[char], start: usize) -> (String, bool, usize) {
+    let digit_at = |index: usize| characters.get(index).is_some_and(|character| character.is_ascii_digit());
+
+    let mut index = start;
+    while digit_at(index) {
+        index += 1;
+    }
+
+    let mut digits: String = characters[start..index].iter().collect();
+    let mut grouped = false;
+
+    // Only a leading group of one to three digits can be followed by groups of three
+    if index - start <= 3 {
+        while characters.get(index) == Some(&',') && (1..=3).all(|offset| digit_at(index + offset)) && !digit_at(index + 4) {
+            digits.extend(&characters[index + 1..index + 4]);
+            grouped = true;
+            index += 4;
+        }
+    }
+
+    (digits, grouped, index)
+}
+
+// A time such as 9:05 or 21:30, optionally followed by am or pm
+fn read_time(characters: &[char], start: usize) -> Option<(usize, String)> {
+    let digit = |index: usize| characters.get(index).and_then(|character| character.to_digit(10));
+
+    let hour_digits = if digit(start + 1).is_some() { 2 } else { 1 };
+    let colon = start + hour_digits;
+
+    if characters.get(colon) != Some(&':') || digit(colon + 3).is_some() {
+        return None;
+    }
+
+    let hour = (start..colon).try_fold(0, |hour, index| Some(hour * 10 + digit(index)?))?;
+    let minute = digit(colon + 1)? * 10 + digit(colon + 2)?;
+
+    if hour > 23 || minute > 59 {
+        return None;
+    }
+
+    let hour_words = integer_words(&hour.to_string());
+
+    let minute_words = match minute {
+        0 => None,
+        1..=9 => Some(format!("OH {}", ONES[minute as usize])),
+        _ => Some(integer_words(&minute.to_string()))
+    };
+
+    let end = colon + 3;
+
+    if let Some((end, meridiem)) = read_meridiem(characters, end).filter(|_| (1..=12).contains(&hour)) {
+        let words = match minute_words {
+            Some(minute_words) => format!("{} {} {}", hour_words, minute_words, meridiem),
+            None => format!("{} {}", hour_words, meridiem)
+        };
+
+        return Some((end, words));
+    }
+
+    let words = match (minute_words, hour) {
+        (Some(minute_words), _) => format!("{} {}", hour_words, minute_words),
+        (None, 1..=12) => format!("{} O'CLOCK", hour_words),
+        (None, _) => format!("{} HUNDRED", hour_words)
+    };
+
+    Some((end, words))
+}
+
+// The end of an am or pm at the index, optionally preceded by a space, and how to spell it. The
+// period after a.m. is left in place, as it may end the sentence.
+fn read_meridiem(characters: &[char], start: usize) -> Option<(usize, &'static str)> {
+    let lowercase_at = |index: usize| characters.get(index).map(char::to_ascii_lowercase);
+
+    let mut index = start;
+    if lowercase_at(index) == Some(' ') {
+        index += 1;
+    }
+
+    let meridiem = match lowercase_at(index) {
+        Some('a') => "AY EM",
+        Some('p') => "PEE EM",
+        _ => return None
+    };
+
+    index += 1;
+    if lowercase_at(index) == Some('.') {
+        index += 1;
+    }
+
+    // The m must end the word, so that a word like "amazing" is not taken for am
+    match lowercase_at(index) {
+        Some('m') if !characters.get(index + 1).is_some_and(|character| character.is_alphanumeric()) => Some((index + 1, meridiem)),
+        _ => None
+    }
+}
+
+// An amount of money, followed by a scale word as in "$1.5 million"
+fn read_amount(characters: &[char], index: usize, currency: Currency, integer: &str, fraction: Option<&str>) -> (usize, String) {
+    let (singular, plural) = currency.major();
+
+    // A scale word after a space moves the unit to the end
+    let scale = SCALES.iter().map(|(_, scale)| *scale).find_map(|scale| {
+        let word = characters.get(index + 1..index + 1 + scale.len())?;
+
+        let matches = characters[index] == ' '
+            && word.iter().zip(scale.chars()).all(|(character, scale)| character.to_ascii_uppercase() == scale)
+            && !characters.get(index + 1 + scale.len()).is_some_and(|character| character.is_alphanumeric());
+
+        matches.then_some((index + 1 + scale.len(), scale))
+    });
+
+    if let Some((end, scale)) = scale {
+        return (end, format!("{} {} {}", decimal_words(integer, fraction), scale, plural));
+    }
+
+    let cents = match fraction {
+        None => Some(0),
+        Some(fraction) if fraction.len() <= 2 => format!("{:0<2}", fraction).parse::<u64>().ok(),
+        Some(_) => None
+    };
+
+    let (Ok(units @ 0..=LARGEST), Some(cents)) = (integer.parse::<u64>(), cents) else {
+        return (index, format!("{} {}", decimal_words(integer, fraction), plural));
+    };
+
+    let (minor_singular, minor_plural) = currency.minor();
+    let major = format!("{} {}", cardinal(units), if units == 1 { singular } else { plural });
+    let minor = format!("{} {}", cardinal(cents), if cents == 1 { minor_singular } else { minor_plural });
+
+    let words = match (units, cents) {
+        (_, 0) => major,
+        (0, _) => minor,
+        _ => format!("{} AND {}", major, minor)
+    };
+
+    (index, words)
+}
+
+fn decimal_words(integer: &str, fraction: Option<&str>) -> String {
+    match fraction {
+        Some(fraction) => format!("{} POINT {}", integer_words(integer), digit_words(fraction)),
+        None => integer_words(integer)
+    }
+}
+
+fn year_or_integer_words(integer: &str, grouped: bool) -> String {
+    match integer.parse::<u64>() {
+        Ok(year @ 1100..=2099) if !grouped && integer.len() == 4 => year_words(year),
+        _ => integer_words(integer)
+    }
+}
+
+// Read as a cardinal, or digit by digit when it has leading zeros or is too large
+fn integer_words(integer: &str) -> String {
+    match integer.parse::<u64>() {
+        Ok(value @ 0..=LARGEST) if integer.len() == 1 || !integer.starts_with('0') => cardinal(value),
+        _ => digit_words(integer)
+    }
+}
+
+fn digit_words(digits: &str) -> String {
+    digits.chars()
+        .filter_map(|digit| digit.to_digit(10))
+        .map(|digit| ONES[digit as usize])
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// A year read in pairs of digits, as in NINETEEN OH FIVE
+fn year_words(year: u64) -> String {
+    let (century, rest) = (year / 100, year % 100);
+
+    match rest {
+        _ if (2000..2010).contains(&year) => cardinal(year),
+        0 => format!("{} HUNDRED", cardinal(century)),
+        1..=9 => format!("{} OH {}", cardinal(century), ONES[rest as usize]),
+        _ => format!("{} {}", cardinal(century), cardinal(rest))
+    }
+}
+
+fn cardinal(value: u64) -> String {
+    if value == 0 {
+        return ONES[0].to_owned();
+    }
+
+    let mut words = Vec::new();
+    let mut rest = value;
+
+    for (scale, name) in SCALES {
+        if rest >= scale {
+            push_hundreds(&mut words, rest / scale);
+            words.push(name);
+            rest %= scale;
+        }
+    }
+
+    push_hundreds(&mut words, rest);
+    words.join(" ")
+}
+
+// Add the words for a number below a thousand, nothing for zero
+fn push_hundreds(words: &mut Vec<&'static str>, value: u64) {
+    let (hundreds, rest) = ((value / 100) as usize, (value % 100) as usize);
+
+    if hundreds > 0 {
+        words.push(ONES[hundreds]);
+        words.push("HUNDRED");
+    }
+
+    match rest {
+        0 => {},
+        1..=19 => words.push(ONES[rest]),
+        _ => {
+            words.push(TENS[rest / 10]);
+
+            if rest % 10 > 0 {
+                words.push(ONES[rest % 10]);
+            }
+        }
+    }
+}
+
+// Turn the last word of a number into an ordinal
+fn ordinal(words: &str) -> String {
+    let (rest, last) = words.rsplit_once(' ').unwrap_or(("", words));
+
+    let last = match last {
+        "ONE" => "FIRST".to_owned(),
+        "TWO" => "SECOND".to_owned(),
+        "THREE" => "THIRD".to_owned(),
+        "FIVE" => "FIFTH".to_owned(),
+        "EIGHT" => "EIGHTH".to_owned(),
+        "NINE" => "NINTH".to_owned(),
+        "TWELVE" => "TWELFTH".to_owned(),
+        _ => match last.strip_suffix('Y') {
+            Some(stem) => format!("{}IETH", stem),
+            None => format!("{}TH", last)
+        }
+    };
+
+    match rest {
+        "" => last,
+        _ => format!("{} {}", rest, last)
+    }
+}
+
+// Turn the last word of a number into a plural, as in NINETEEN EIGHTIES
+fn plural(words: &str) -> String {
+    if let Some(stem) = words.strip_suffix('Y') {
+        format!("{}IES", stem)
+    } else if words.ends_with('X') {
+        format!("{}ES", words)
+    } else {
+        format!("{}S", words)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers() {
+        assert_eq!(normalize("0, 7, 13 and 40"), "ZERO, SEVEN, THIRTEEN and FORTY");
+        assert_eq!(normalize("Score: 1,234,567!"), "Score: ONE MILLION TWO HUNDRED THIRTY FOUR THOUSAND FIVE HUNDRED SIXTY SEVEN!");
+        assert_eq!(normalize("100000 and 2,000,000,000,001"), "ONE HUNDRED THOUSAND and TWO TRILLION ONE");
+        assert_eq!(normalize("Agent 007"), "Agent ZERO ZERO SEVEN");
+        assert_eq!(normalize("1st 2nd 3rd 11th 22ND 40th 112th"), "FIRST SECOND THIRD ELEVENTH TWENTY SECOND FORTIETH ONE HUNDRED TWELFTH");
+        assert_eq!(normalize("3.14 and -5 and -0.5%"), "THREE POINT ONE FOUR and MINUS FIVE and MINUS ZERO POINT FIVE PERCENT");
+        assert_eq!(normalize("Level 3."), "Level THREE.");
+        assert_eq!(normalize("I scored 25 amazing goals"), "I scored TWENTY FIVE amazing goals");
+        assert_eq!(normalize("Wait 3 amps"), "Wait THREE amps");
+        assert_eq!(normalize("13 pm"), "THIRTEEN pm");
+    }
+
+    #[test]
+    fn currency() {
+        assert_eq!(normalize("$1"), "ONE DOLLAR");
+        assert_eq!(normalize("$4.99"), "FOUR DOLLARS AND NINETY NINE CENTS");
+        assert_eq!(normalize("$0.01 or £0.5"), "ONE CENT or FIFTY PENCE");
+        assert_eq!(normalize("€2.00, -$3"), "TWO EUROS, MINUS THREE DOLLARS");
+        assert_eq!(normalize("$1.5 million"), "ONE POINT FIVE MILLION DOLLARS");
+        assert_eq!(normalize("$1.005"), "ONE POINT ZERO ZERO FIVE DOLLARS");
+    }
+
+    #[test]
+    fn years_and_times() {
+        assert_eq!(normalize("In 1984, 1905, 1900, 2001 and 2024"), "In NINETEEN EIGHTY FOUR, NINETEEN OH FIVE, NINETEEN HUNDRED, TWO THOUSAND ONE and TWENTY TWENTY FOUR");
+        assert_eq!(normalize("The 1990s, the 60s and 1,984"), "The NINETEEN NINETIES, the SIXTIES and ONE THOUSAND NINE HUNDRED EIGHTY FOUR");
+        assert_eq!(normalize("At 9:05, 3:00, 14:00 and 21:30"), "At NINE OH FIVE, THREE O'CLOCK, FOURTEEN HUNDRED and TWENTY ONE THIRTY");
+        assert_eq!(normalize("At 7pm or 11:30 a.m."), "At SEVEN PEE EM or ELEVEN THIRTY AY EM.");
+        assert_eq!(normalize("9:30 amazing"), "NINE THIRTY amazing");
+    }
+
+    #[test]
+    fn words_are_left_alone() {
+        for text in ["R2D2", "MP3", "1.2.3", "10x", "25:61", "13pm", "1:02:03", "1,23", "3amps", "9:30pmx"] {
+            assert_eq!(normalize(text), text);
+        }
+    }
+
+    #[test]
+    fn original_range() {
+        let normalized = Normalized::new("Win $5 now");
+
+        assert_eq!(normalized.text, "Win FIVE DOLLARS now");
+        assert_eq!(normalized.original_range(0..4), 0..4);
+        assert_eq!(normalized.original_range(4..9), 4..6);
+        assert_eq!(normalized.original_range(9..16), 6..6);
+        assert_eq!(normalized.original_range(16..20), 6..10);
+    }
+
+    #[test]
+    fn pronunciations() {
+        for (_, phonemes) in PRONUNCIATIONS {
+            assert!(crate::parser::parse_phonemes(phonemes).is_ok(), "{}", phonemes);
+        }
+    }
+}
@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use once_cell::sync::Lazy;
+
+use super::{rules, ReciterError, ReciterRule};
+use crate::parser;
+
+static ENGLISH: Lazy<Arc<RuleSet>> = Lazy::new(|| {
+    let mut rule_set = RuleSet::new();
+
+    // The letter rules go first, the character rules include an "(A)" that must not take
+    // precedence over the rules for A
+    for (pattern, replacement) in rules::RULES.iter().chain(rules::CHARACTER_RULES) {
+        rule_set.add(pattern, replacement).unwrap_or_else(|err| {
+            panic!("Could not instantiate reciter rule for {:?} -> {:?} ({:?})", pattern, replacement, err)
+        });
+    }
+
+    Arc::new(rule_set)
+});
+
+#[derive(Debug)]
+pub enum RuleSetError {
+    IO(std::io::Error),
+    MissingSeparator {
+        line: usize
+    },
+    InvalidRule {
+        line: usize,
+        err: ReciterError
+    }
+}
+
+impl std::error::Error for RuleSetError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RuleSetError::IO(err) => Some(err),
+            RuleSetError::InvalidRule { err, .. } => Some(err),
+            RuleSetError::MissingSeparator { .. } => None
+        }
+    }
+}
+
+impl std::fmt::Display for RuleSetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            RuleSetError::IO(err) => write!(f, "Could not read rule set ({})", err),
+            RuleSetError::MissingSeparator { line } => write!(f, "Missing \" -> \" on line {}", line),
+            RuleSetError::InvalidRule { line, err } => write!(f, "Invalid rule on line {} ({})", line, err)
+        }
+    }
+}
+
+impl From<std::io::Error> for RuleSetError {
+    fn from(err: std::io::Error) -> Self {
+        RuleSetError::IO(err)
+    }
+}
+
+/// The rules the reciter uses to convert text to phonemes.
+///
+/// A rule such as `#:(E)D ` replaces the source text between the parentheses with phonemes when
+/// the text before and after it matches the prefix and suffix. Letters and apostrophes in the
+/// context are matched literally, the other characters are classes:
+///
+/// * `' '` Anything but a letter or apostrophe, such as a word boundary
+/// * `#` A vowel or Y
+/// * `.` One of B D G J L M N R V W Z
+/// * `&` One of C G J S X Z, or the digraphs CH and SH
+/// * `@` A voiced consonant (one of D J L N R S T Z)
+/// * `^` A consonant
+/// * `+` One of E I Y
+/// * `:` Any number of consonants, including none
+/// * `%` A suffix: ING, E, ER, ES, ED, ELY or EFUL (only after the source)
+///
+/// For each position in the text the rules for the character at that position are tried in the
+/// order they were added, and the first matching rule is applied.
+///
+/// A rule set file has one rule per line, separated from its phonemes by " -> ". Everything
+/// before the separator is part of the rule, so a suffix that ends in a space is followed by two
+/// spaces. The source must consist of uppercase letters, digits, apostrophes or punctuation,
+/// and the phonemes must parse. Blank lines and lines starting with ";" are ignored:
+///
+/// ```text
+/// ; THE at the start of a word, followed by a vowel
+///  (THE)# -> DHIY
+///  (THE) -> DHAX
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RuleSet {
+    rules: Vec<ReciterRule>,
+
+    // The positions in `rules` of the rules for each first source character
+    by_character: HashMap<char, Vec<usize>>
+}
+
+impl RuleSet {
+    /// Create an empty rule set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The English rules of the original reciter.
+    pub fn english() -> Self {
+        ENGLISH.as_ref().clone()
+    }
+
+    pub(crate) fn shared_english() -> Arc<RuleSet> {
+        ENGLISH.clone()
+    }
+
+    /// Read a rule set file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, RuleSetError> {
+        std::fs::read_to_string(path)?.parse()
+    }
+
+    /// Add a rule such as `" (THE)#"` with the phonemes that replace the source, after the
+    /// existing rules.
+    pub fn add(&mut self, rule: &str, phonemes: &str) -> Result<(), ReciterError> {
+        let rule = ReciterRule::new(rule, phonemes)?;
+
+        self.by_character.entry(rule.source[0]).or_default().push(self.rules.len());
+        self.rules.push(rule);
+
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// The rules whose source starts with the character, in order, or `None` if there are none.
+    pub(super) fn rules_for(&self, character: char) -> Option<impl Iterator<Item = &ReciterRule>> {
+        self.by_character.get(&character)
+            .map(|positions| positions.iter().map(|position| &self.rules[*position]))
+    }
+}
+
+impl std::str::FromStr for RuleSet {
+    type Err = RuleSetError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut rule_set = RuleSet::new();
+
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index + 1;
+
+            // Leading and trailing spaces of a rule are significant, so lines are not trimmed
+            if line.trim().is_empty() || line.starts_with(';') {
+                continue;
+            }
+
+            // Editors may strip the space after the separator of a rule without phonemes
+            let (rule, phonemes) = line.split_once(" -> ")
+                .or_else(|| line.strip_suffix(" ->").map(|rule| (rule, "")))
+                .ok_or(RuleSetError::MissingSeparator { line: line_number })?;
+
+            // The phonemes are checked here, so that mistakes are reported with their line
+            rule_set.add(rule, phonemes)
+                .and_then(|_| parser::parse_phonemes(phonemes).map(|_| ()).map_err(ReciterError::InvalidPhonemes))
+                .map_err(|err| RuleSetError::InvalidRule {
+                line: line_number,
+                err
+            })?;
+        }
+
+        Ok(rule_set)
+    }
+}
+
+impl std::fmt::Display for RuleSet {
+    /// Write the rules in the rule set file format.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for rule in &self.rules {
+            writeln!(f, "{} -> {}", rule, rule.target)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let rule_set: RuleSet = "; Comment\n\n (THE)# -> DHIY\n (X)  -> EH4KS\n".parse().unwrap();
+
+        assert_eq!(rule_set.len(), 2);
+        assert_eq!(rule_set.rules[1].suffix, [' ']);
+        assert_eq!(rule_set.rules[1].target, "EH4KS");
+
+        assert!(matches!("(A) AE".parse::<RuleSet>(), Err(RuleSetError::MissingSeparator { line: 1 })));
+        assert!(matches!("A) -> AE".parse::<RuleSet>(), Err(RuleSetError::InvalidRule { line: 1, err: ReciterError::MissingOpenParenthesis })));
+        assert!(matches!("\n(A -> AE".parse::<RuleSet>(), Err(RuleSetError::InvalidRule { line: 2, err: ReciterError::MissingCloseParenthesis })));
+        assert!(matches!("() -> AE".parse::<RuleSet>(), Err(RuleSetError::InvalidRule { err: ReciterError::EmptySource, .. })));
+        assert!(matches!("%(A) -> AE".parse::<RuleSet>(), Err(RuleSetError::InvalidRule { err: ReciterError::InvalidContextCharacter('%'), .. })));
+        assert!(matches!("(A)! -> AE".parse::<RuleSet>(), Err(RuleSetError::InvalidRule { err: ReciterError::InvalidContextCharacter('!'), .. })));
+        assert!(matches!(" (the) -> DHAX".parse::<RuleSet>(), Err(RuleSetError::InvalidRule { err: ReciterError::InvalidSourceCharacter('t'), .. })));
+        assert!(matches!("(A B) -> AE".parse::<RuleSet>(), Err(RuleSetError::InvalidRule { err: ReciterError::InvalidSourceCharacter(' '), .. })));
+        assert!(matches!("(A) -> AE\n(B) -> BXYZ".parse::<RuleSet>(), Err(RuleSetError::InvalidRule { line: 2, err: ReciterError::InvalidPhonemes(_) })));
+    }
+
+    #[test]
+    fn english_round_trip() {
+        let english = RuleSet::english();
+
+        assert_eq!(english.len(), rules::RULES.len() + rules::CHARACTER_RULES.len());
+        assert_eq!(english.to_string().parse::<RuleSet>().unwrap(), english);
+    }
+}
@@ -1,8 +1,23 @@
 use std::cmp::Ordering;
 
-use crate::parser::Phoneme;
+use crate::Compatibility;
+use crate::parser::{Phoneme, PhonemeKind};
 
+mod resampler;
+mod sample;
 mod tests;
+mod timeline;
+mod voice;
+
+use resampler::Resampler;
+use timeline::Tracker;
+
+pub use sample::{Sample, apply_gain, normalize};
+pub use timeline::{Event, TimelineEntry};
+pub use voice::{Preset, Voice, VoiceBuilder, VoiceError};
+
+/// The sample rate SAM renders at. Voices with a different sample rate are resampled.
+pub const SAMPLE_RATE: u32 = 22050;
 
 // Frequency data for each of the three formant waveforms
 const FREQUENCY_DATA: (&[u8], &[u8], &[u8]) = (
@@ -394,20 +409,20 @@ fn set_mouth_and_throat(mouth: u8, throat: u8) -> FrequencyData {
 }
 
 #[derive(Debug, Eq, PartialEq)]
-struct Frame {
-    pitch: u8,
+pub(crate) struct Frame {
+    pub(crate) pitch: u8,
 
     // Frequencies
-    f1: u8,
-    f2: u8,
-    f3: u8,
+    pub(crate) f1: u8,
+    pub(crate) f2: u8,
+    pub(crate) f3: u8,
 
     // Amplitudes
-    a1: u8,
-    a2: u8,
-    a3: u8,
+    pub(crate) a1: u8,
+    pub(crate) a2: u8,
+    pub(crate) a3: u8,
 
-    sampled_consonant_flag: u8
+    pub(crate) sampled_consonant_flag: u8
 }
 
 impl Frame {
@@ -450,8 +465,13 @@ fn add_inflection(inflection: Inflection, frames: &mut [Frame]) {
     // FIXME: Explain this fix better, it's not obvious
     // ML : A =, fixes a problem with invalid pitch with '.'
     loop {
-        // TODO: Bounds checking
-        a = frames[position].pitch;
+        // Reading past the end of the frames results in undefined in the reference
+        // implementation, which ends the search without applying any inflection
+        let Some(frame) = frames.get(position) else {
+            return;
+        };
+
+        a = frame.pitch;
         if a != 127 {
             break;
         }
@@ -486,29 +506,30 @@ fn create_frames(pitch: u8, phonemes: &[crate::parser::Phoneme], frequency_data:
     let mut frames = Vec::new();
 
     for phoneme in phonemes {
-        if phoneme.index == crate::parser::PHONEME_PERIOD {
+        if phoneme.kind == PhonemeKind::Period {
             add_inflection(Inflection::Falling, &mut frames);
-        } else if phoneme.index == crate::parser::PHONEME_QUESTION_MARK {
+        } else if phoneme.kind == PhonemeKind::QuestionMark {
             add_inflection(Inflection::Rising, &mut frames);
         }
 
         // get the stress amount (more stress = higher pitch)
         let phase1 = STRESS_PITCH_TABLE[phoneme.stress as usize];
+        let index = phoneme.kind.index();
 
         // get number of frames to write
         // copy from the source to the frames list
         frames.extend((0..phoneme.length).map(|_| Frame {
             pitch: (pitch.wrapping_add(phase1)),
 
-            f1: frequency_data.f1[phoneme.index],
-            f2: frequency_data.f2[phoneme.index],
-            f3: frequency_data.f3[phoneme.index],
+            f1: frequency_data.f1[index],
+            f2: frequency_data.f2[index],
+            f3: frequency_data.f3[index],
 
-            a1: AMPLITUDE_DATA[phoneme.index].0,
-            a2: AMPLITUDE_DATA[phoneme.index].1,
-            a3: AMPLITUDE_DATA[phoneme.index].2,
+            a1: AMPLITUDE_DATA[index].0,
+            a2: AMPLITUDE_DATA[index].1,
+            a3: AMPLITUDE_DATA[index].2,
 
-            sampled_consonant_flag: SAMPLED_CONSONANT_FLAGS[phoneme.index]
+            sampled_consonant_flag: SAMPLED_CONSONANT_FLAGS[index]
         }));
     }
 
@@ -609,7 +630,7 @@ where
         // The reference implementation has a bug where the starting frame can sometimes be a
         // negative index. In JavaScript such an array lookup will result in a NaN value, causing
         // the interpolator to write a NaN value, and creating NaN feedback for the rest of the
-        // interpolation sequence.
+        // interpolation sequence. Corrected mode never passes a negative starting frame.
         if position < 1 {
             table(position as usize, Some(0));
             continue;
@@ -639,12 +660,12 @@ where
     }
 }
 
-fn create_transitions(frames: &mut Vec<Frame>, phonemes: &[Phoneme]) -> usize {
+fn create_transitions(frames: &mut Vec<Frame>, phonemes: &[Phoneme], compatibility: Compatibility) -> usize {
     let mut boundary: usize = 0;
 
     for position in 0..(phonemes.len() - 1) {
-        let phoneme      = phonemes[position].index;
-        let next_phoneme = phonemes[position + 1].index;
+        let phoneme      = phonemes[position].kind.index();
+        let next_phoneme = phonemes[position + 1].kind.index();
 
         // get the ranking of each phoneme
         let next_rank = BLEND_RANK[next_phoneme];
@@ -664,7 +685,7 @@ fn create_transitions(frames: &mut Vec<Frame>, phonemes: &[Phoneme]) -> usize {
 
         boundary = boundary.wrapping_add(phonemes[position].length as usize);
 
-        let trans_end        = boundary + in_blend_frames as usize;
+        let trans_end    = boundary + in_blend_frames as usize;
         let trans_start  = boundary as isize - out_blend_frames as isize;
         let trans_length = out_blend_frames + in_blend_frames; // total transition
 
@@ -682,32 +703,43 @@ fn create_transitions(frames: &mut Vec<Frame>, phonemes: &[Phoneme]) -> usize {
             // TODO: The start position for pitch doesn't seem correct, needs verification
             let pitch = (frames[boundary + next_width as usize].pitch as i16 - frames[boundary - cur_width as usize].pitch as i16) as i8;
 
-            interpolate(
-                cur_width + next_width,
-                |index, value| {
-                    if let Some(value) = value {
-                        // The reference implementation has a bug where it tries to interpolate off
-                        // of the end of the frame list. This creates new frames that shouldn't
-                        // actually exist. To prevent any indexing panics a new frame is added when
-                        // writing off of the end. The new frame is populated with zeroes to mimic
-                        // the behavior of "undefined" values in JavaScript in a safe way.
-                        if index == frames.len() {
-                            frames.push(Frame::new());
+            // The reference implementation starts interpolating before the first frame when the
+            // first phoneme is shorter than its blend length, see interpolate. Corrected mode
+            // starts at the first frame instead.
+            let (pitch_start, pitch_width) = match compatibility {
+                Compatibility::SamJs => (trans_start, cur_width + next_width),
+                Compatibility::Corrected => {
+                    let skipped = (-trans_start).max(0);
+                    (trans_start + skipped, (cur_width + next_width).saturating_sub(skipped as u8))
+                }
+            };
+
+            if pitch_width > 0 {
+                interpolate(
+                    pitch_width,
+                    |index, value| {
+                        if let Some(value) = value {
+                            // The reference implementation has a bug where it tries to interpolate
+                            // off of the end of the frame list. This creates new frames that
+                            // shouldn't actually exist. To prevent any indexing panics a new frame
+                            // is added when writing off of the end. The new frame is populated
+                            // with zeroes to mimic the behavior of "undefined" values in
+                            // JavaScript in a safe way. Corrected mode ignores these writes.
+                            if index == frames.len() && compatibility == Compatibility::SamJs {
+                                frames.push(Frame::new());
+                            }
+
+                            if let Some(frame) = frames.get_mut(index) {
+                                frame.pitch = value;
+                            }
                         }
 
-                        frames[index].pitch = value
-                    }
-
-                    // TODO: This check is only necessary for debugging
-                    if index == frames.len() {
-                        0
-                    } else {
-                        frames[index].pitch
-                    }
-                },
-                trans_start,
-                pitch
-            );
+                        frames.get(index).map_or(0, |frame| frame.pitch)
+                    },
+                    pitch_start,
+                    pitch
+                );
+            }
 
             //let mut buffer = frames[range.clone()].iter().map(|frame| frame.pitch).collect::<Vec<_>>();
             //interpolate_buffer(&mut buffer, Some(pitch));
@@ -720,10 +752,27 @@ fn create_transitions(frames: &mut Vec<Frame>, phonemes: &[Phoneme]) -> usize {
             // interpolate one frame beyond the end of the frame list. This causes the change delta
             // to become NaN, causing the interpolator to leave the frames untouched (this might
             // not be the desired result). To prevent any panics the interpolation is skipped when
-            // trans_end goes beyond the frame list.
-            if trans_end >= frames.len() {
-                continue;
-            }
+            // trans_end goes beyond the frame list. Corrected mode instead interpolates towards
+            // the last frame, and starts at the first frame when trans_start is negative.
+            let (trans_start, trans_end, trans_length) = match compatibility {
+                Compatibility::SamJs => {
+                    if trans_end >= frames.len() {
+                        continue;
+                    }
+
+                    (trans_start, trans_end, trans_length)
+                },
+                Compatibility::Corrected => {
+                    let start = trans_start.max(0);
+                    let end = trans_end.min(frames.len() - 1);
+
+                    if end as isize - start < 2 {
+                        continue;
+                    }
+
+                    (start, end, (end as isize - start) as u8)
+                }
+            };
 
             let change = frames.get(trans_start as usize).map(|frame| frames[trans_end].f1 as i8 - frame.f1 as i8).unwrap_or(0);
             interpolate(
@@ -829,16 +878,16 @@ const AMPLITUDE_RESCALE_TABLE: &[u8] = &[
     0x04, 0x05, 0x06, 0x08, 0x09, 0x0B, 0x0D, 0x0F
 ];
 
-struct PreparedFrames {
+pub(crate) struct PreparedFrames {
     // TODO: How does this relate to frames.len()?
-    frame_count: usize,
-    frames: Vec<Frame>
+    pub(crate) frame_count: usize,
+    pub(crate) frames: Vec<Frame>
 }
 
-fn prepare_frames(phonemes: &[Phoneme], pitch: u8, mouth: u8, throat: u8, sing_mode: bool) -> PreparedFrames {
+pub(crate) fn prepare_frames(phonemes: &[Phoneme], pitch: u8, mouth: u8, throat: u8, sing_mode: bool, compatibility: Compatibility) -> PreparedFrames {
     let frequency_data = set_mouth_and_throat(mouth, throat);
     let mut frames = create_frames(pitch, phonemes, &frequency_data);
-    let t = create_transitions(&mut frames, phonemes);
+    let t = create_transitions(&mut frames, phonemes, compatibility);
 
     if !sing_mode {
         // Assing pitch contour
@@ -871,8 +920,29 @@ const TIMETABLE: &[[u8; 5]] = &[
     [199,   0,   0,  54,  54]  // voiced sample 1
 ];
 
+/// The number of times per second the frame processor steps the formant oscillators. Each step
+/// advances the phase of a formant by its frequency value, out of 256 for a full cycle.
+pub(crate) fn step_rate() -> f64 {
+    SAMPLE_RATE as f64 * 50.0 / TIMETABLE[0][0] as f64
+}
+
+/// The frequency in Hz of a formant frequency value.
+pub(crate) fn formant_frequency(value: u8) -> f64 {
+    value as f64 * step_rate() / 256.0
+}
+
+/// The number of frames played per second at a speed, not counting the time the sampled
+/// consonants take.
+pub(crate) fn frame_rate(speed: u8) -> f64 {
+    step_rate() / speed as f64
+}
+
 struct OutputBuffer {
     buffer: Vec<u8>,
+
+    // Number of samples that have been drained from the front of the buffer
+    offset: usize,
+
     position: usize,
     old_timetable_index: usize
 }
@@ -881,6 +951,7 @@ impl OutputBuffer {
     fn new(size: usize) -> Self {
         Self {
             buffer: vec![0; size],
+            offset: 0,
             position: 0,
             old_timetable_index: 0
         }
@@ -892,14 +963,34 @@ impl OutputBuffer {
 
         self.old_timetable_index = index;
 
+        let start = self.position / 50 - self.offset;
+
+        // Grow the buffer when streaming, new samples start out as silence
+        if self.buffer.len() < start + array.len() {
+            self.buffer.resize(start + array.len(), 0);
+        }
+
         // Write a little bit in advance
         for (index, sample) in array.into_iter().enumerate() {
-            self.buffer[self.position / 50 + index] = sample;
+            self.buffer[start + index] = sample;
         }
     }
 
     fn get(&self) -> &[u8] {
-        &self.buffer[..(self.position / 50)]
+        &self.buffer[..(self.position / 50 - self.offset)]
+    }
+
+    /// Remove and return the samples that can no longer be overwritten. Samples are always
+    /// written starting at the current position, so everything before it is final.
+    fn drain_finalized(&mut self) -> std::vec::Drain<'_, u8> {
+        let end = self.position / 50 - self.offset;
+        self.offset += end;
+        self.buffer.drain(..end)
+    }
+
+    /// The number of samples up to the current position, including the drained ones.
+    fn sample_position(&self) -> usize {
+        self.position / 50
     }
 
     fn write(&mut self, index: usize, a: u8) {
@@ -912,7 +1003,7 @@ impl OutputBuffer {
 }
 
 // Sampled data for consonants, consisting of five 256-byte sections
-const SAMPLE_TABLE: &[u8] = &[
+pub(crate) const SAMPLE_TABLE: &[u8] = &[
     //00  T', S, Z  (coronal)
     0x38, 0x84, 0x6B, 0x19, 0xC6, 0x63, 0x18, 0x86,
     0x73, 0x98, 0xC6, 0xB1, 0x1C, 0xCA, 0x31, 0x8C,
@@ -1230,37 +1321,78 @@ fn render_sample(output: &mut OutputBuffer, last_sample_offset: usize, consonant
     last_sample_offset
 }
 
-fn sinus(x: u8) -> i8 {
+pub(crate) fn sinus(x: u8) -> i8 {
     ((2.0 * std::f32::consts::PI * (x as f32 / 256.0)).sin() * 127.0) as i8
 }
 
-fn process_frames(output: &mut OutputBuffer, speed: u8, prepared_frames: &PreparedFrames) {
-    let mut frame_count = prepared_frames.frame_count;
-    let frames = &prepared_frames.frames;
-
-    let mut speed_counter = speed;
-    let mut phase1 = 0;
-    let mut phase2 = 0;
-    let mut phase3 = 0;
-    let mut last_sample_offset = 0;
-    let mut pos = 0;
+/// The state of the frame processor, which renders the prepared frames one step at a time so the
+/// output can be consumed while it is being generated.
+struct FrameProcessor {
+    speed: u8,
+    frame_count: usize,
+    speed_counter: u8,
+    phase1: u32,
+    phase2: u32,
+    phase3: u32,
+    last_sample_offset: usize,
+    pos: usize,
 
     // These two variables are not supposed to underflow, however due to a bug in the reference
-    // implementation glottal_pulse can be set to NaN, which will lock it to that value.
-    let mut glottal_pulse = frames[0].pitch as isize;
-    let mut mem38 = (glottal_pulse * 3) / 4;
+    // implementation glottal_pulse can be set to NaN, which will lock it to that value. Here a
+    // frame with a pitch of zero has the same effect: the counter skips past zero and the formant
+    // phases are never reset again.
+    glottal_pulse: isize,
+    mem38: isize,
 
-    while frame_count > 0 {
+    compatibility: Compatibility
+}
+
+impl FrameProcessor {
+    fn new(speed: u8, prepared_frames: &PreparedFrames, compatibility: Compatibility) -> Self {
+        let glottal_pulse = prepared_frames.frames.first().map_or(0, |frame| Self::glottal_pulse_length(frame, compatibility));
+
+        Self {
+            speed,
+            frame_count: prepared_frames.frame_count,
+            speed_counter: speed,
+            phase1: 0,
+            phase2: 0,
+            phase3: 0,
+            last_sample_offset: 0,
+            pos: 0,
+            glottal_pulse,
+            mem38: (glottal_pulse * 3) / 4,
+            compatibility
+        }
+    }
+
+    // Corrected mode lets a glottal pulse last at least one step, so a zero pitch can not lock up
+    // the glottal pulse counter.
+    fn glottal_pulse_length(frame: &Frame, compatibility: Compatibility) -> isize {
+        match compatibility {
+            Compatibility::SamJs => frame.pitch as isize,
+            Compatibility::Corrected => frame.pitch.max(1) as isize
+        }
+    }
+
+    /// Process a single iteration of the frame loop. Returns false when all frames have been
+    /// processed.
+    fn step(&mut self, output: &mut OutputBuffer, frames: &[Frame]) -> bool {
+        if self.frame_count == 0 {
+            return false;
+        }
+
+        let pos = self.pos;
         let flags = frames[pos].sampled_consonant_flag;
 
         // unvoiced sampled phoneme?
         if flags & 248 != 0 {
-            last_sample_offset = render_sample(output, last_sample_offset, flags, frames[pos & 0xff].pitch);
+            self.last_sample_offset = render_sample(output, self.last_sample_offset, flags, frames[pos & 0xff].pitch);
 
             // skip ahead two in the phoneme buffer
-            pos += 2;
-            frame_count -= 2;
-            speed_counter = speed;
+            self.pos += 2;
+            self.frame_count -= 2;
+            self.speed_counter = self.speed;
         } else {
             {
                 // Rectangle wave consisting of:
@@ -1271,9 +1403,9 @@ fn process_frames(output: &mut OutputBuffer, speed: u8, prepared_frames: &Prepar
                 let mut ary = [0_u8; 5];
 
                 // TODO: Check if u16 is sufficient for these values
-                let mut /* unsigned int */ p1: u32 = phase1 * 256; // Fixed point integers because we need to divide later on
-                let mut /* unsigned int */ p2: u32 = phase2 * 256;
-                let mut /* unsigned int */ p3: u32 = phase3 * 256;
+                let mut /* unsigned int */ p1: u32 = self.phase1 * 256; // Fixed point integers because we need to divide later on
+                let mut /* unsigned int */ p2: u32 = self.phase2 * 256;
+                let mut /* unsigned int */ p3: u32 = self.phase3 * 256;
 
                 for sample in ary.iter_mut() {
                     // Sine oscillators
@@ -1304,68 +1436,248 @@ fn process_frames(output: &mut OutputBuffer, speed: u8, prepared_frames: &Prepar
                 output.ary(0, ary);
             }
 
-            speed_counter -= 1;
+            self.speed_counter -= 1;
 
-            if speed_counter == 0 {
-                pos += 1; //go to next amplitude
+            if self.speed_counter == 0 {
+                self.pos += 1; //go to next amplitude
 
                 // decrement the frame count
-                frame_count -= 1;
+                self.frame_count -= 1;
 
-                if frame_count == 0 {
-                    return;
+                if self.frame_count == 0 {
+                    return false;
                 }
 
-                speed_counter = speed;
+                self.speed_counter = self.speed;
             }
 
-            glottal_pulse -= 1;
+            self.glottal_pulse -= 1;
 
-            if glottal_pulse != 0 {
+            if self.glottal_pulse != 0 {
                 // not finished with a glottal pulse
 
-                mem38 -= 1;
+                self.mem38 -= 1;
 
                 // within the first 75% of the glottal pulse?
                 // is the count non-zero and the sampled flag is zero?
-                if mem38 != 0 || flags == 0 {
+                if self.mem38 != 0 || flags == 0 {
                     // update the phase of the formants
                     // TODO: we should have a switch to disable this, it causes a pretty nice voice without the masking!
-                    phase1 += frames[pos].f1 as u32; // & 0xFF;
-                    phase2 += frames[pos].f2 as u32; // & 0xFF;
-                    phase3 += frames[pos].f3 as u32; // & 0xFF;
+                    self.phase1 += frames[self.pos].f1 as u32; // & 0xFF;
+                    self.phase2 += frames[self.pos].f2 as u32; // & 0xFF;
+                    self.phase3 += frames[self.pos].f3 as u32; // & 0xFF;
 
-                    continue;
+                    return true;
                 }
 
                 // voiced sampled phonemes interleave the sample with the
                 // glottal pulse. The sample flag is non-zero, so render
                 // the sample for the phoneme.
-                last_sample_offset = render_sample(output, last_sample_offset, flags, frames[pos & 0xFF].pitch);
+                self.last_sample_offset = render_sample(output, self.last_sample_offset, flags, frames[self.pos & 0xFF].pitch);
             }
         }
 
         // The reference implementation has a bug and tries to read beyond the end of the frame
         // list. In JavaScript this returns undefined, but in rust this results in a panic.
-        if frame_count == 0 {
-            break;
+        if self.frame_count == 0 {
+            return false;
         }
 
-        glottal_pulse = frames[pos].pitch as isize;
-        if glottal_pulse > 0 {
-            mem38 = (glottal_pulse * 3) / 4;
+        self.glottal_pulse = Self::glottal_pulse_length(&frames[self.pos], self.compatibility);
+        if self.glottal_pulse > 0 {
+            self.mem38 = (self.glottal_pulse * 3) / 4;
         }
 
         // reset the formant wave generators to keep them in
         // sync with the glottal pulse
-        phase1 = 0;
-        phase2 = 0;
-        phase3 = 0;
+        self.phase1 = 0;
+        self.phase2 = 0;
+        self.phase3 = 0;
+
+        true
+    }
+}
+
+fn process_frames(output: &mut OutputBuffer, speed: u8, prepared_frames: &PreparedFrames, compatibility: Compatibility) {
+    let mut processor = FrameProcessor::new(speed, prepared_frames, compatibility);
+
+    while processor.step(output, &prepared_frames.frames) {}
+}
+
+/// A renderer that produces its output incrementally.
+///
+/// The renderer implements `Iterator<Item = u8>`, yielding the same unsigned 8 bit samples as
+/// [`render_with`] as soon as they have been synthesized. Other sample types are available
+/// through [`Renderer::samples`], and the output can be pulled in chunks of any sample type using
+/// [`Renderer::fill`].
+///
+/// While the samples are consumed, [`Renderer::next_event`] reports where each phoneme starts and
+/// ends, for synchronizing animation with the audio.
+pub struct Renderer {
+    prepared_frames: PreparedFrames,
+    processor: FrameProcessor,
+    output: OutputBuffer,
+    resampler: Option<Resampler>,
+    resampled: Vec<f32>,
+    pending: std::collections::VecDeque<f32>,
+    tracker: Tracker,
+
+    // The number of samples that have been consumed
+    produced: usize,
+
+    finished: bool
+}
+
+impl Renderer {
+    pub fn new(phonemes: &[Phoneme], voice: &Voice) -> Self {
+        let prepared_frames = if phonemes.is_empty() {
+            PreparedFrames {
+                frame_count: 0,
+                frames: Vec::new()
+            }
+        } else {
+            prepare_frames(phonemes, voice.pitch(), voice.mouth(), voice.throat(), voice.sing_mode(), voice.compatibility())
+        };
+
+        let processor = FrameProcessor::new(voice.speed(), &prepared_frames, voice.compatibility());
+
+        let resampler = if voice.sample_rate() == SAMPLE_RATE {
+            None
+        } else {
+            Some(Resampler::new(SAMPLE_RATE, voice.sample_rate()))
+        };
+
+        let mut tracker = Tracker::new(phonemes, SAMPLE_RATE, voice.sample_rate());
+        tracker.advance(0, 0);
+
+        Self {
+            prepared_frames,
+            processor,
+            output: OutputBuffer::new(0),
+            resampler,
+            resampled: Vec::new(),
+            pending: std::collections::VecDeque::new(),
+            tracker,
+            produced: 0,
+            finished: false
+        }
+    }
+
+    /// Remove the next phoneme event that occurs at or before the next sample, or any remaining
+    /// event once all samples have been consumed. Polling for events after pulling each chunk of
+    /// samples keeps them in sync with the audio.
+    pub fn next_event(&mut self) -> Option<Event> {
+        let exhausted = self.finished && self.pending.is_empty();
+
+        self.tracker.next_event(self.produced, exhausted)
+    }
+
+    /// Fill the buffer with the next samples. Returns the number of samples written, which is only
+    /// less than the buffer length when the end of the output has been reached.
+    pub fn fill<S: Sample>(&mut self, buffer: &mut [S]) -> usize {
+        let mut count = 0;
+
+        for target in buffer.iter_mut() {
+            match self.next_float() {
+                Some(sample) => *target = S::from_float(sample),
+                None => break
+            }
+
+            count += 1;
+        }
+
+        count
+    }
+
+    /// Turn the renderer into an iterator over samples of another type, such as `i16` or `f32`.
+    pub fn samples<S: Sample>(mut self) -> impl Iterator<Item = S> {
+        std::iter::from_fn(move || self.next_float().map(S::from_float))
+    }
+
+    fn next_float(&mut self) -> Option<f32> {
+        loop {
+            if let Some(sample) = self.pending.pop_front() {
+                self.produced += 1;
+                return Some(sample);
+            }
+
+            if self.finished {
+                return None;
+            }
+
+            self.finished = !self.processor.step(&mut self.output, &self.prepared_frames.frames);
+
+            if self.finished {
+                self.tracker.finish(self.output.sample_position());
+            } else {
+                self.tracker.advance(self.processor.pos, self.output.sample_position());
+            }
+
+            if let Some(resampler) = &mut self.resampler {
+                resampler.process(self.output.drain_finalized().map(u8::to_float), &mut self.resampled);
+
+                if self.finished {
+                    resampler.finish(&mut self.resampled);
+                }
+
+                self.pending.extend(self.resampled.drain(..));
+            } else {
+                self.pending.extend(self.output.drain_finalized().map(u8::to_float));
+            }
+        }
+    }
+}
+
+impl Iterator for Renderer {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        self.next_float().map(u8::from_float)
     }
 }
 
 pub fn render(phonemes: &[Phoneme], pitch: u8, mouth: u8, throat: u8, speed: u8, sing_mode: bool) -> Vec<u8> {
-    let prepared_frames = prepare_frames(phonemes, pitch, mouth, throat, sing_mode);
+    render_compatible(phonemes, pitch, mouth, throat, speed, sing_mode, Compatibility::SamJs)
+}
+
+/// Render the phonemes using the parameters of the given voice.
+pub fn render_with(phonemes: &[Phoneme], voice: &Voice) -> Vec<u8> {
+    render_samples(phonemes, voice)
+}
+
+/// Render the phonemes using the parameters of the given voice, converting the output to the
+/// requested sample type, such as `i16` or `f32`.
+pub fn render_samples<S: Sample>(phonemes: &[Phoneme], voice: &Voice) -> Vec<S> {
+    let samples = render_compatible(phonemes, voice.pitch(), voice.mouth(), voice.throat(), voice.speed(), voice.sing_mode(), voice.compatibility())
+        .into_iter()
+        .map(u8::to_float)
+        .collect::<Vec<_>>();
+
+    let samples = if voice.sample_rate() == SAMPLE_RATE {
+        samples
+    } else {
+        Resampler::resample(&samples, SAMPLE_RATE, voice.sample_rate())
+    };
+
+    samples.into_iter().map(S::from_float).collect()
+}
+
+/// Render the phonemes like [`render_samples`], along with the range of samples that belongs to
+/// each phoneme.
+pub fn render_with_timeline<S: Sample>(phonemes: &[Phoneme], voice: &Voice) -> (Vec<S>, Vec<TimelineEntry>) {
+    let mut renderer = Renderer::new(phonemes, voice);
+    let samples = std::iter::from_fn(|| renderer.next_float().map(S::from_float)).collect();
+    let timeline = timeline::to_timeline(std::iter::from_fn(|| renderer.next_event()));
+
+    (samples, timeline)
+}
+
+fn render_compatible(phonemes: &[Phoneme], pitch: u8, mouth: u8, throat: u8, speed: u8, sing_mode: bool, compatibility: Compatibility) -> Vec<u8> {
+    if phonemes.is_empty() {
+        return Vec::new();
+    }
+
+    let prepared_frames = prepare_frames(phonemes, pitch, mouth, throat, sing_mode, compatibility);
 
     // Create output buffer
     let mut output = OutputBuffer::new(
@@ -1376,7 +1688,7 @@ pub fn render(phonemes: &[Phoneme], pitch: u8, mouth: u8, throat: u8, speed: u8,
         ).ceil() as usize
     );
 
-    process_frames(&mut output, speed, &prepared_frames);
+    process_frames(&mut output, speed, &prepared_frames, compatibility);
 
     output.get().to_vec()
 }
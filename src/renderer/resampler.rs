@@ -0,0 +1,226 @@
+use std::collections::VecDeque;
+
+// Number of zero crossings of the sinc function on either side of the kernel center
+const ZERO_CROSSINGS: usize = 16;
+
+// Number of kernel table entries per input sample, values in between are interpolated linearly
+const KERNEL_RESOLUTION: usize = 128;
+
+// Place the cutoff slightly below the Nyquist frequency, so the transition band of the windowed
+// kernel does not leak aliases into the output
+const ROLLOFF: f64 = 0.95;
+
+/// A band-limited resampler using a windowed sinc kernel.
+///
+/// The input is processed as a stream, so it can be fed with the output of the renderer while it
+/// is being generated. The signal is extended with its first and last sample beyond its ends.
+pub(crate) struct Resampler {
+    input_rate: u64,
+    output_rate: u64,
+
+    // Half the kernel width in input samples
+    half_width: usize,
+
+    // One side of the symmetric kernel, sampled KERNEL_RESOLUTION times per input sample
+    kernel: Vec<f32>,
+
+    // The input samples that may still contribute to the output, history[0] is input sample
+    // number `first`
+    history: VecDeque<f32>,
+    first: u64,
+
+    received: u64,
+    produced: u64
+}
+
+impl Resampler {
+    pub(crate) fn new(input_rate: u32, output_rate: u32) -> Self {
+        // The cutoff frequency relative to the input Nyquist frequency. When downsampling this
+        // removes everything that does not fit below the output Nyquist frequency.
+        let cutoff = (output_rate as f64 / input_rate as f64).min(1.0) * ROLLOFF;
+        let half_width = (ZERO_CROSSINGS as f64 / cutoff).ceil() as usize;
+
+        let kernel = (0..=half_width * KERNEL_RESOLUTION).map(|index| {
+            let x = index as f64 / KERNEL_RESOLUTION as f64;
+
+            (cutoff * sinc(cutoff * x) * blackman(x / half_width as f64)) as f32
+        }).collect();
+
+        Self {
+            input_rate: input_rate as u64,
+            output_rate: output_rate as u64,
+            half_width,
+            kernel,
+            history: VecDeque::new(),
+            first: 0,
+            received: 0,
+            produced: 0
+        }
+    }
+
+    /// Resample a complete signal at once.
+    pub(crate) fn resample(input: &[f32], input_rate: u32, output_rate: u32) -> Vec<f32> {
+        let mut resampler = Self::new(input_rate, output_rate);
+        let mut output = Vec::new();
+
+        resampler.process(input.iter().copied(), &mut output);
+        resampler.finish(&mut output);
+
+        output
+    }
+
+    /// Feed input samples, appending the output samples that can be computed so far.
+    pub(crate) fn process<I: IntoIterator<Item = f32>>(&mut self, input: I, output: &mut Vec<f32>) {
+        for sample in input {
+            self.history.push_back(sample);
+            self.received += 1;
+        }
+
+        // An output sample is complete when all input samples under its kernel have arrived
+        while self.received > 0 {
+            let (center, _) = self.position(self.produced);
+            if center + self.half_width as u64 >= self.received {
+                break;
+            }
+
+            output.push(self.next_sample());
+        }
+
+        self.trim();
+    }
+
+    /// Signal the end of the input, appending the remaining output samples.
+    pub(crate) fn finish(&mut self, output: &mut Vec<f32>) {
+        let length = (self.received * self.output_rate).div_ceil(self.input_rate);
+
+        while self.produced < length {
+            output.push(self.next_sample());
+        }
+
+        self.trim();
+    }
+
+    // The input sample at or before output sample `index`, and the fractional distance to it
+    fn position(&self, index: u64) -> (u64, f64) {
+        let position = index * self.input_rate;
+
+        (position / self.output_rate, (position % self.output_rate) as f64 / self.output_rate as f64)
+    }
+
+    fn input(&self, index: i64) -> f32 {
+        // Extend the signal with its first and last sample
+        let index = index.clamp(self.first as i64, self.received as i64 - 1) as u64;
+
+        self.history[(index - self.first) as usize]
+    }
+
+    fn kernel(&self, distance: f64) -> f32 {
+        let position = distance.abs() * KERNEL_RESOLUTION as f64;
+        let index = position as usize;
+
+        if index + 1 >= self.kernel.len() {
+            return 0.0;
+        }
+
+        let fraction = (position - index as f64) as f32;
+
+        self.kernel[index] + (self.kernel[index + 1] - self.kernel[index]) * fraction
+    }
+
+    fn next_sample(&mut self) -> f32 {
+        let (center, fraction) = self.position(self.produced);
+        let half_width = self.half_width as i64;
+
+        let mut sum = 0.0;
+        for offset in (1 - half_width)..=half_width {
+            sum += self.input(center as i64 + offset) * self.kernel(offset as f64 - fraction);
+        }
+
+        self.produced += 1;
+
+        sum
+    }
+
+    // Drop the input samples that no longer contribute to the output, keeping at least one to
+    // extend the signal with
+    fn trim(&mut self) {
+        let (center, _) = self.position(self.produced);
+        let needed = (center + 1).saturating_sub(self.half_width as u64).min(self.received.saturating_sub(1));
+
+        while self.first < needed {
+            self.history.pop_front();
+            self.first += 1;
+        }
+    }
+}
+
+fn sinc(x: f64) -> f64 {
+    if x == 0.0 {
+        1.0
+    } else {
+        let x = std::f64::consts::PI * x;
+        x.sin() / x
+    }
+}
+
+// Blackman window over -1.0..=1.0
+fn blackman(x: f64) -> f64 {
+    let x = std::f64::consts::PI * x;
+
+    0.42 + 0.5 * x.cos() + 0.08 * (2.0 * x).cos()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn length() {
+        assert_eq!(Resampler::resample(&[0.0; 22050], 22050, 44100).len(), 44100);
+        assert_eq!(Resampler::resample(&[0.0; 22050], 22050, 48000).len(), 48000);
+        assert_eq!(Resampler::resample(&[0.0; 100], 22050, 8000).len(), 37);
+        assert!(Resampler::resample(&[], 22050, 48000).is_empty());
+    }
+
+    #[test]
+    fn preserves_dc() {
+        for sample in Resampler::resample(&[0.5; 1000], 22050, 48000) {
+            assert!((sample - 0.5).abs() < 0.001, "{}", sample);
+        }
+    }
+
+    #[test]
+    fn streaming_matches_batch() {
+        let input = (0..5000).map(|index| ((index as f32) * 0.05).sin()).collect::<Vec<_>>();
+        let expected = Resampler::resample(&input, 22050, 44100);
+
+        let mut resampler = Resampler::new(22050, 44100);
+        let mut output = Vec::new();
+        for chunk in input.chunks(77) {
+            resampler.process(chunk.iter().copied(), &mut output);
+        }
+        resampler.finish(&mut output);
+
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn removes_aliases() {
+        // A 10 kHz tone can not be represented at 16 kHz and should be filtered out
+        let input = (0..22050).map(|index| (index as f32 * 2.0 * std::f32::consts::PI * 10000.0 / 22050.0).sin()).collect::<Vec<_>>();
+        let output = Resampler::resample(&input, 22050, 16000);
+
+        let rms = (output[1000..15000].iter().map(|sample| sample * sample).sum::<f32>() / 14000.0).sqrt();
+        assert!(rms < 0.01, "{}", rms);
+    }
+
+    #[test]
+    fn passes_tones() {
+        // A 1 kHz tone survives upsampling with its amplitude intact
+        let input = (0..22050).map(|index| (index as f32 * 2.0 * std::f32::consts::PI * 1000.0 / 22050.0).sin()).collect::<Vec<_>>();
+        let output = Resampler::resample(&input, 22050, 48000);
+
+        let rms = (output[1000..47000].iter().map(|sample| sample * sample).sum::<f32>() / 46000.0).sqrt();
+        assert!((rms - std::f32::consts::FRAC_1_SQRT_2).abs() < 0.01, "{}", rms);
+    }
+}
@@ -0,0 +1,100 @@
+/// A sample type the renderer can produce.
+///
+/// Conversions go through floating point samples in the range -1.0..=1.0. The unsigned 8 bit
+/// samples of SAM are centered on 0x80, which maps to 0.0.
+pub trait Sample: Copy {
+    /// Convert a floating point sample, clipping it to the range of the type.
+    fn from_float(sample: f32) -> Self;
+
+    /// Convert to a floating point sample.
+    fn to_float(self) -> f32;
+}
+
+impl Sample for u8 {
+    fn from_float(sample: f32) -> Self {
+        (sample * 128.0 + 128.0).round().clamp(0.0, 255.0) as u8
+    }
+
+    fn to_float(self) -> f32 {
+        (self as f32 - 128.0) / 128.0
+    }
+}
+
+impl Sample for i16 {
+    fn from_float(sample: f32) -> Self {
+        (sample * 32768.0).round().clamp(-32768.0, 32767.0) as i16
+    }
+
+    fn to_float(self) -> f32 {
+        self as f32 / 32768.0
+    }
+}
+
+impl Sample for f32 {
+    // Floating point samples are passed through as they are, so a gain above one does not clip
+    fn from_float(sample: f32) -> Self {
+        sample
+    }
+
+    fn to_float(self) -> f32 {
+        self
+    }
+}
+
+/// Multiply the samples by `gain`, clipping the result to the range of the sample type.
+pub fn apply_gain<S: Sample>(samples: &mut [S], gain: f32) {
+    for sample in samples {
+        *sample = S::from_float(sample.to_float() * gain);
+    }
+}
+
+/// Scale the samples so that the loudest one reaches `peak`, where 1.0 is full scale. Silence is
+/// left unchanged.
+pub fn normalize<S: Sample>(samples: &mut [S], peak: f32) {
+    let loudest = samples.iter().fold(0.0_f32, |loudest, sample| loudest.max(sample.to_float().abs()));
+
+    if loudest > 0.0 {
+        apply_gain(samples, peak / loudest);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conversions() {
+        assert_eq!(0x80_u8.to_float(), 0.0);
+        assert_eq!(0x00_u8.to_float(), -1.0);
+        assert_eq!(i16::from_float(0x80_u8.to_float()), 0);
+        assert_eq!(i16::from_float(0xff_u8.to_float()), 0x7f00);
+        assert_eq!(i16::from_float(0x00_u8.to_float()), -0x8000);
+        assert_eq!(i16::from_float(2.0), i16::MAX);
+
+        for sample in 0..=255_u8 {
+            assert_eq!(u8::from_float(sample.to_float()), sample);
+            assert_eq!(u8::from_float(i16::from_float(sample.to_float()).to_float()), sample);
+        }
+    }
+
+    #[test]
+    fn gain() {
+        let mut samples = [0x80_u8, 0xa0, 0x60, 0xf0];
+        apply_gain(&mut samples, 2.0);
+
+        assert_eq!(samples, [0x80, 0xc0, 0x40, 0xff]);
+    }
+
+    #[test]
+    fn normalization() {
+        let mut samples = [0.0_f32, 0.25, -0.5];
+        normalize(&mut samples, 1.0);
+
+        assert_eq!(samples, [0.0, 0.5, -1.0]);
+
+        let mut silence = [0x80_u8; 4];
+        normalize(&mut silence, 1.0);
+
+        assert_eq!(silence, [0x80; 4]);
+    }
+}
@@ -1,4 +1,5 @@
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests {
     use crate::renderer::*;
 
@@ -98,7 +99,7 @@ mod tests {
         let phonemes = tuples.iter().map(|&[index, length, stress]| {
             crate::parser::Phoneme {
                 length,
-                index: index as usize,
+                kind: crate::parser::PhonemeKind::from_index(index as usize).unwrap(),
                 stress
             }
         }).collect::<Vec<_>>();
@@ -292,12 +293,12 @@ mod tests {
         let phonemes = tuples.iter().map(|&[index, length, stress]| {
             crate::parser::Phoneme {
                 length,
-                index: index as usize,
+                kind: crate::parser::PhonemeKind::from_index(index as usize).unwrap(),
                 stress
             }
         }).collect::<Vec<_>>();
 
-        let result = create_transitions(&mut frames, &phonemes);
+        let result = create_transitions(&mut frames, &phonemes, crate::Compatibility::SamJs);
 
         assert_eq!(result, 63);
 
@@ -382,6 +383,7 @@ mod tests {
         assert_eq!(&frames.iter().map(|frame| frame.a1).collect::<Vec<_>>(), &amplitude1, "a1 is not equal");
         assert_eq!(&frames.iter().map(|frame| frame.a2).collect::<Vec<_>>(), &amplitude2, "a2 is not equal");
         assert_eq!(&frames.iter().map(|frame| frame.a3).collect::<Vec<_>>(), &amplitude3, "a3 is not equal");
+        assert_eq!(frames, expected, "Frames are not equal");
     }
 
     #[test]
@@ -465,12 +467,12 @@ mod tests {
         let phonemes = tuples.iter().map(|&[index, length, stress]| {
             crate::parser::Phoneme {
                 length,
-                index: index as usize,
+                kind: crate::parser::PhonemeKind::from_index(index as usize).unwrap(),
                 stress
             }
         }).collect::<Vec<_>>();
 
-        let result = create_transitions(&mut frames, &phonemes);
+        let result = create_transitions(&mut frames, &phonemes, crate::Compatibility::SamJs);
 
         assert_eq!(result, 29);
 
@@ -541,6 +543,7 @@ mod tests {
         assert_eq!(&frames.iter().map(|frame| frame.a1).collect::<Vec<_>>(), &amplitude1, "a1 is not equal");
         assert_eq!(&frames.iter().map(|frame| frame.a2).collect::<Vec<_>>(), &amplitude2, "a2 is not equal");
         assert_eq!(&frames.iter().map(|frame| frame.a3).collect::<Vec<_>>(), &amplitude3, "a3 is not equal");
+        assert_eq!(frames, expected, "Frames are not equal");
     }
 
 
@@ -635,13 +638,13 @@ mod tests {
         }).collect();
 
         let prepared_frames = PreparedFrames {
-            frame_count: frame_count as u8,
+            frame_count,
             frames
         };
 
         let mut buffer = OutputBuffer::new(863654);
 
-        process_frames(&mut buffer, speed, &prepared_frames);
+        process_frames(&mut buffer, speed, &prepared_frames, crate::Compatibility::SamJs);
 
         let result = buffer.get();
 
@@ -1906,4 +1909,184 @@ mod tests {
 
         assert_eq!(result, &expected);
     }
+
+    #[test]
+    fn test_renderer_matches_render() {
+        let voices = [
+            Voice::default(),
+            Voice::preset(Preset::LittleRobot),
+            Voice::builder().pitch(100).speed(80).mouth(127).throat(127).build().unwrap(),
+            Voice::builder().compatibility(crate::Compatibility::Corrected).build().unwrap(),
+            Voice::builder().sample_rate(48000).build().unwrap()
+        ];
+
+        for text in ["HELLO WORLD", "TEST", "MY NAME IS SAM.", "WHAT IS THIS?"] {
+            let recited = crate::reciter::text_to_phonemes(text).unwrap();
+            let phonemes = crate::parser::parse_phonemes(&recited).unwrap();
+
+            for voice in &voices {
+                let expected = render_with(&phonemes, voice);
+
+                let streamed = Renderer::new(&phonemes, voice).collect::<Vec<_>>();
+                assert_eq!(streamed, expected, "Streamed output differs for {:?}", text);
+
+                // Pull the output in odd sized chunks
+                let mut renderer = Renderer::new(&phonemes, voice);
+                let mut chunked = Vec::new();
+                let mut chunk = [0_u8; 333];
+
+                loop {
+                    let count = renderer.fill(&mut chunk);
+                    chunked.extend_from_slice(&chunk[..count]);
+
+                    if count < chunk.len() {
+                        break;
+                    }
+                }
+
+                assert_eq!(chunked, expected, "Chunked output differs for {:?}", text);
+            }
+        }
+    }
+
+    #[test]
+    fn test_render_sample_rate() {
+        let phonemes = crate::parser::parse_phonemes("/HEHLOW5").unwrap();
+        let native = render_with(&phonemes, &Voice::default());
+
+        for sample_rate in [8000, 44100, 48000] {
+            let voice = Voice::builder().sample_rate(sample_rate).build().unwrap();
+            let resampled = render_with(&phonemes, &voice);
+
+            let expected = (native.len() as u64 * sample_rate as u64).div_ceil(SAMPLE_RATE as u64);
+            assert_eq!(resampled.len() as u64, expected);
+        }
+    }
+
+    #[test]
+    fn test_render_sample_types() {
+        let phonemes = crate::parser::parse_phonemes("/HEHLOW5").unwrap();
+
+        for voice in [Voice::default(), Voice::builder().sample_rate(44100).build().unwrap()] {
+            let float = render_samples::<f32>(&phonemes, &voice);
+            let signed = render_samples::<i16>(&phonemes, &voice);
+
+            assert_eq!(Renderer::new(&phonemes, &voice).samples::<f32>().collect::<Vec<_>>(), float);
+            assert_eq!(Renderer::new(&phonemes, &voice).samples::<i16>().collect::<Vec<_>>(), signed);
+            assert_eq!(signed, float.iter().map(|sample| i16::from_float(*sample)).collect::<Vec<_>>());
+        }
+
+        // At the native sample rate the other types are exact conversions of the 8 bit samples
+        let voice = Voice::default();
+        let expected = render_with(&phonemes, &voice).into_iter()
+            .map(|sample| ((sample as i16) - 128) << 8)
+            .collect::<Vec<_>>();
+
+        assert_eq!(render_samples::<i16>(&phonemes, &voice), expected);
+    }
+
+    #[test]
+    fn test_render_with_timeline() {
+        let phonemes = crate::parser::parse_phonemes("/HEHLOW5, MAY NEYM IHZ SAEM.").unwrap();
+
+        for voice in [Voice::default(), Voice::builder().sample_rate(48000).build().unwrap()] {
+            let (samples, timeline) = render_with_timeline::<u8>(&phonemes, &voice);
+
+            assert_eq!(samples, render_with(&phonemes, &voice));
+            assert_eq!(timeline.len(), phonemes.len());
+            assert_eq!(timeline[0].start, 0);
+            assert_eq!(timeline[timeline.len() - 1].end, samples.len());
+
+            for (index, (entry, phoneme)) in timeline.iter().zip(&phonemes).enumerate() {
+                assert_eq!(entry.index, index);
+                assert_eq!(entry.kind, phoneme.kind);
+                assert_eq!(entry.stress, phoneme.stress);
+                assert!(entry.start <= entry.end);
+
+                // Phonemes with frames have samples
+                assert_eq!(entry.start < entry.end, phoneme.length > 0, "{:?}", entry);
+            }
+
+            for pair in timeline.windows(2) {
+                assert_eq!(pair[0].end, pair[1].start);
+            }
+        }
+    }
+
+    #[test]
+    fn test_renderer_events() {
+        let phonemes = crate::parser::parse_phonemes("/HEHLOW5, MAY NEYM IHZ SAEM.").unwrap();
+        let voice = Voice::default();
+        let (_, timeline) = render_with_timeline::<u8>(&phonemes, &voice);
+
+        let mut renderer = Renderer::new(&phonemes, &voice);
+        let mut events = Vec::new();
+        let mut produced = 0;
+        let mut chunk = [0_u8; 100];
+
+        loop {
+            // Events are reported no later than the chunk that contains them
+            while let Some(event) = renderer.next_event() {
+                assert!(event.sample() <= produced, "{:?} after {}", event, produced);
+                events.push(event);
+            }
+
+            let count = renderer.fill(&mut chunk);
+            produced += count;
+
+            if count == 0 {
+                break;
+            }
+        }
+
+        assert_eq!(events.len(), phonemes.len() * 2);
+        assert!(matches!(events[0], Event::Start { index: 0, sample: 0, .. }));
+        assert_eq!(timeline::to_timeline(events), timeline);
+    }
+
+    #[test]
+    fn test_renderer_empty() {
+        assert_eq!(Renderer::new(&[], &Voice::default()).next(), None);
+    }
+
+    #[test]
+    fn test_corrected_transitions_stay_within_frames() {
+        let mut grown = false;
+
+        for text in ["GXUL3-5", ",IX", "AOUXAA5-5", "S6AO7D"] {
+            let phonemes = crate::parser::parse_phonemes(text).unwrap();
+            let length = phonemes.iter().map(|phoneme| phoneme.length as usize).sum::<usize>();
+
+            let samjs = prepare_frames(&phonemes, 64, 128, 128, false, crate::Compatibility::SamJs);
+            let corrected = prepare_frames(&phonemes, 64, 128, 128, false, crate::Compatibility::Corrected);
+
+            grown |= samjs.frames.len() > length;
+            assert_eq!(corrected.frames.len(), length, "Frames were added for {:?}", text);
+            assert_eq!(corrected.frame_count, samjs.frame_count);
+        }
+
+        assert!(grown, "None of the inputs interpolate off of the end of the frames");
+    }
+
+    #[test]
+    fn test_corrected_glottal_pulse() {
+        let mut frames = vec![Frame::new(), Frame::new(), Frame::new()];
+        frames.iter_mut().for_each(|frame| frame.a1 = 8);
+
+        let prepared_frames = PreparedFrames {
+            frame_count: frames.len(),
+            frames
+        };
+
+        let mut output = OutputBuffer::new(0);
+        let mut processor = FrameProcessor::new(8, &prepared_frames, crate::Compatibility::Corrected);
+        while processor.step(&mut output, &prepared_frames.frames) {
+            assert!(processor.glottal_pulse >= 0);
+        }
+
+        // A zero pitch locks up the glottal pulse counter in the reference implementation
+        let mut processor = FrameProcessor::new(8, &prepared_frames, crate::Compatibility::SamJs);
+        while processor.step(&mut output, &prepared_frames.frames) {}
+        assert!(processor.glottal_pulse < 0);
+    }
 }
@@ -0,0 +1,171 @@
+use std::collections::VecDeque;
+
+use crate::parser::{Phoneme, PhonemeKind};
+
+/// A phoneme boundary in the output of a [`Renderer`](super::Renderer).
+///
+/// Every phoneme of the rendered sequence gets a start and an end event, including the internal
+/// phonemes the parser inserted and phonemes without any frames, whose start and end coincide.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Event {
+    /// The phoneme at `index` in the rendered sequence starts at output sample `sample`.
+    Start { index: usize, kind: PhonemeKind, stress: u8, sample: usize },
+
+    /// The phoneme at `index` ends at output sample `sample`, which is where the next one starts.
+    End { index: usize, sample: usize }
+}
+
+impl Event {
+    /// The output sample the event occurs at.
+    pub fn sample(&self) -> usize {
+        match self {
+            Event::Start { sample, .. } | Event::End { sample, .. } => *sample
+        }
+    }
+}
+
+/// The range of output samples that belongs to a phoneme, see [`render_with_timeline`](super::render_with_timeline).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TimelineEntry {
+    /// The position of the phoneme in the rendered sequence.
+    pub index: usize,
+    pub kind: PhonemeKind,
+    pub stress: u8,
+
+    /// The first sample of the phoneme.
+    pub start: usize,
+
+    /// The sample after the last sample of the phoneme.
+    pub end: usize
+}
+
+/// Turns the progress of the frame processor into events.
+///
+/// Phonemes occupy consecutive runs of frames, so a phoneme starts at the output position where
+/// the processor reaches its first frame. Phonemes that are never reached because the processor
+/// stopped early start at the end of the output.
+pub(crate) struct Tracker {
+    phonemes: Vec<(PhonemeKind, u8)>,
+
+    // The first frame of each phoneme
+    frame_starts: Vec<usize>,
+
+    // The number of phonemes that have started, and whether the last one has ended
+    started: usize,
+    ended: bool,
+
+    // Positions are converted from the native sample rate to the output sample rate
+    input_rate: u64,
+    output_rate: u64,
+
+    events: VecDeque<Event>
+}
+
+impl Tracker {
+    pub(crate) fn new(phonemes: &[Phoneme], input_rate: u32, output_rate: u32) -> Self {
+        let frame_starts = phonemes.iter()
+            .scan(0, |frame, phoneme| {
+                let start = *frame;
+                *frame += phoneme.length as usize;
+                Some(start)
+            })
+            .collect();
+
+        Self {
+            phonemes: phonemes.iter().map(|phoneme| (phoneme.kind, phoneme.stress)).collect(),
+            frame_starts,
+            started: 0,
+            ended: false,
+            input_rate: input_rate as u64,
+            output_rate: output_rate as u64,
+            events: VecDeque::new()
+        }
+    }
+
+    /// Start the phonemes whose first frame is at or before `frame`, which starts at native sample
+    /// `sample`.
+    pub(crate) fn advance(&mut self, frame: usize, sample: usize) {
+        while self.started < self.phonemes.len() && self.frame_starts[self.started] <= frame {
+            self.start(sample);
+        }
+    }
+
+    /// Start the remaining phonemes and end the last one at native sample `sample`, the end of the
+    /// output.
+    pub(crate) fn finish(&mut self, sample: usize) {
+        while self.started < self.phonemes.len() {
+            self.start(sample);
+        }
+
+        if self.started > 0 && !self.ended {
+            self.events.push_back(Event::End {
+                index: self.started - 1,
+                sample: self.scale(sample)
+            });
+        }
+
+        self.ended = true;
+    }
+
+    /// Remove the next event if it occurs at or before output sample `produced`, which is the next
+    /// sample to be consumed, or once the end of the output has been reached.
+    pub(crate) fn next_event(&mut self, produced: usize, finished: bool) -> Option<Event> {
+        let event = self.events.front()?;
+
+        if event.sample() <= produced || finished {
+            self.events.pop_front()
+        } else {
+            None
+        }
+    }
+
+    fn start(&mut self, sample: usize) {
+        let index = self.started;
+        let sample = self.scale(sample);
+
+        if index > 0 {
+            self.events.push_back(Event::End {
+                index: index - 1,
+                sample
+            });
+        }
+
+        let (kind, stress) = self.phonemes[index];
+        self.events.push_back(Event::Start {
+            index,
+            kind,
+            stress,
+            sample
+        });
+
+        self.started += 1;
+    }
+
+    // The first output sample at or after native sample `sample`, matching the length of the
+    // resampled output
+    fn scale(&self, sample: usize) -> usize {
+        (sample as u64 * self.output_rate).div_ceil(self.input_rate) as usize
+    }
+}
+
+/// Collect the start and end events of the phonemes into timeline entries.
+pub(crate) fn to_timeline<I: IntoIterator<Item = Event>>(events: I) -> Vec<TimelineEntry> {
+    let mut timeline: Vec<TimelineEntry> = Vec::new();
+
+    for event in events {
+        match event {
+            Event::Start { index, kind, stress, sample } => timeline.push(TimelineEntry {
+                index,
+                kind,
+                stress,
+                start: sample,
+                end: sample
+            }),
+            Event::End { index, sample } => if let Some(entry) = timeline.get_mut(index) {
+                entry.end = sample;
+            }
+        }
+    }
+
+    timeline
+}
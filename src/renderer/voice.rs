@@ -0,0 +1,332 @@
+use crate::Compatibility;
+
+use super::SAMPLE_RATE;
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum VoiceError {
+    InvalidPitch(u8),
+    InvalidSpeed(u8),
+    InvalidSampleRate(u32),
+    UnknownPreset(String)
+}
+
+impl std::error::Error for VoiceError {}
+
+impl std::fmt::Display for VoiceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            VoiceError::InvalidPitch(pitch) => write!(f, "Invalid pitch {}, must be in the range 1..=255", pitch),
+            VoiceError::InvalidSpeed(speed) => write!(f, "Invalid speed {}, must be in the range 1..=255", speed),
+            VoiceError::InvalidSampleRate(rate) => write!(f, "Invalid sample rate {}, must be in the range 4000..=192000", rate),
+            VoiceError::UnknownPreset(name) => write!(f, "Unknown voice preset {:?}", name)
+        }
+    }
+}
+
+/// The named voices from the original SAM manual.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Preset {
+    Sam,
+    Elf,
+    LittleRobot,
+    StuffyGuy,
+    LittleOldLady,
+    ExtraTerrestrial
+}
+
+impl Preset {
+    /// All presets, in the order they are listed in the manual.
+    pub const ALL: &'static [Preset] = &[
+        Preset::Sam,
+        Preset::Elf,
+        Preset::LittleRobot,
+        Preset::StuffyGuy,
+        Preset::LittleOldLady,
+        Preset::ExtraTerrestrial
+    ];
+
+    /// The name of the preset as printed in the manual.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Preset::Sam => "SAM",
+            Preset::Elf => "Elf",
+            Preset::LittleRobot => "Little Robot",
+            Preset::StuffyGuy => "Stuffy Guy",
+            Preset::LittleOldLady => "Little Old Lady",
+            Preset::ExtraTerrestrial => "Extra-Terrestrial"
+        }
+    }
+
+    // (speed, pitch, throat, mouth), in the order the manual lists them
+    fn parameters(&self) -> (u8, u8, u8, u8) {
+        match self {
+            Preset::Sam => (72, 64, 128, 128),
+            Preset::Elf => (72, 64, 110, 160),
+            Preset::LittleRobot => (92, 60, 190, 190),
+            Preset::StuffyGuy => (82, 72, 110, 105),
+            Preset::LittleOldLady => (82, 32, 145, 145),
+            Preset::ExtraTerrestrial => (100, 64, 150, 200)
+        }
+    }
+}
+
+impl std::fmt::Display for Preset {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl std::str::FromStr for Preset {
+    type Err = VoiceError;
+
+    /// Look up a preset by name. Case, spaces, dashes and underscores are ignored, so "Little
+    /// Robot", "little-robot" and "LITTLE_ROBOT" all refer to the same preset.
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        fn normalize(name: &str) -> String {
+            name.chars()
+                .filter(|character| character.is_ascii_alphanumeric())
+                .map(|character| character.to_ascii_lowercase())
+                .collect()
+        }
+
+        let normalized = normalize(name);
+
+        Preset::ALL.iter()
+            .find(|preset| normalize(preset.name()) == normalized)
+            .copied()
+            .ok_or_else(|| VoiceError::UnknownPreset(name.to_owned()))
+    }
+}
+
+/// The set of parameters that shape the voice produced by the renderer.
+///
+/// A voice can only be constructed through a [`VoiceBuilder`] (or [`Voice::default`]), which
+/// guarantees that all of its parameters are within range.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Voice {
+    pitch: u8,
+    speed: u8,
+    mouth: u8,
+    throat: u8,
+    sing_mode: bool,
+    compatibility: Compatibility,
+    sample_rate: u32
+}
+
+impl Voice {
+    /// Create a builder initialized with the default voice settings.
+    pub fn builder() -> VoiceBuilder {
+        VoiceBuilder::new()
+    }
+
+    /// Create one of the named voices from the original SAM manual.
+    pub fn preset(preset: Preset) -> Self {
+        let (speed, pitch, throat, mouth) = preset.parameters();
+
+        Self {
+            pitch,
+            speed,
+            mouth,
+            throat,
+            sing_mode: false,
+            compatibility: Compatibility::SamJs,
+            sample_rate: SAMPLE_RATE
+        }
+    }
+
+    /// Create a builder initialized with the settings of this voice, for tweaking an existing
+    /// voice such as a preset.
+    pub fn to_builder(&self) -> VoiceBuilder {
+        VoiceBuilder {
+            voice: self.clone()
+        }
+    }
+
+    /// The base pitch. Note that a lower value results in a higher pitched voice.
+    pub fn pitch(&self) -> u8 {
+        self.pitch
+    }
+
+    /// The number of samples each frame is rendered for. Higher values result in slower speech.
+    pub fn speed(&self) -> u8 {
+        self.speed
+    }
+
+    /// The mouth size, which scales the first formant frequency.
+    pub fn mouth(&self) -> u8 {
+        self.mouth
+    }
+
+    /// The throat size, which scales the second formant frequency.
+    pub fn throat(&self) -> u8 {
+        self.throat
+    }
+
+    /// Whether the pitch contour derived from the first formant is disabled.
+    pub fn sing_mode(&self) -> bool {
+        self.sing_mode
+    }
+
+    /// Whether the renderer reproduces the bugs of the reference implementation.
+    pub fn compatibility(&self) -> Compatibility {
+        self.compatibility
+    }
+
+    /// The sample rate of the output. SAM renders at [`SAMPLE_RATE`], other rates are resampled.
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+}
+
+impl Default for Voice {
+    /// The classic SAM voice.
+    fn default() -> Self {
+        Self::preset(Preset::Sam)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct VoiceBuilder {
+    voice: Voice
+}
+
+impl VoiceBuilder {
+    pub fn new() -> Self {
+        Self {
+            voice: Voice::default()
+        }
+    }
+
+    pub fn pitch(mut self, pitch: u8) -> Self {
+        self.voice.pitch = pitch;
+        self
+    }
+
+    pub fn speed(mut self, speed: u8) -> Self {
+        self.voice.speed = speed;
+        self
+    }
+
+    pub fn mouth(mut self, mouth: u8) -> Self {
+        self.voice.mouth = mouth;
+        self
+    }
+
+    pub fn throat(mut self, throat: u8) -> Self {
+        self.voice.throat = throat;
+        self
+    }
+
+    pub fn sing_mode(mut self, sing_mode: bool) -> Self {
+        self.voice.sing_mode = sing_mode;
+        self
+    }
+
+    pub fn compatibility(mut self, compatibility: Compatibility) -> Self {
+        self.voice.compatibility = compatibility;
+        self
+    }
+
+    pub fn sample_rate(mut self, sample_rate: u32) -> Self {
+        self.voice.sample_rate = sample_rate;
+        self
+    }
+
+    /// Validate the parameters and create the voice.
+    pub fn build(self) -> Result<Voice, VoiceError> {
+        // A pitch of zero means the glottal pulse never completes
+        if self.voice.pitch == 0 {
+            return Err(VoiceError::InvalidPitch(self.voice.pitch));
+        }
+
+        // A speed of zero underflows the frame counter in the renderer
+        if self.voice.speed == 0 {
+            return Err(VoiceError::InvalidSpeed(self.voice.speed));
+        }
+
+        if !(4000..=192000).contains(&self.voice.sample_rate) {
+            return Err(VoiceError::InvalidSampleRate(self.voice.sample_rate));
+        }
+
+        Ok(self.voice)
+    }
+}
+
+impl Default for VoiceBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_is_classic_sam() {
+        let voice = Voice::default();
+
+        assert_eq!(voice.pitch(), 64);
+        assert_eq!(voice.speed(), 72);
+        assert_eq!(voice.mouth(), 128);
+        assert_eq!(voice.throat(), 128);
+        assert!(!voice.sing_mode());
+        assert_eq!(voice.compatibility(), Compatibility::SamJs);
+        assert_eq!(voice.sample_rate(), SAMPLE_RATE);
+
+        assert_eq!(Voice::builder().build(), Ok(voice));
+    }
+
+    #[test]
+    fn builder_sets_parameters() {
+        let voice = Voice::builder()
+            .pitch(100)
+            .speed(80)
+            .mouth(127)
+            .throat(126)
+            .sing_mode(true)
+            .build()
+            .unwrap();
+
+        assert_eq!(voice.pitch(), 100);
+        assert_eq!(voice.speed(), 80);
+        assert_eq!(voice.mouth(), 127);
+        assert_eq!(voice.throat(), 126);
+        assert!(voice.sing_mode());
+    }
+
+    #[test]
+    fn presets() {
+        let elf = Voice::preset(Preset::Elf);
+
+        assert_eq!(elf.speed(), 72);
+        assert_eq!(elf.pitch(), 64);
+        assert_eq!(elf.throat(), 110);
+        assert_eq!(elf.mouth(), 160);
+
+        assert_eq!(Voice::preset(Preset::Sam), Voice::default());
+
+        for preset in Preset::ALL {
+            assert_eq!(Voice::preset(*preset).to_builder().build(), Ok(Voice::preset(*preset)));
+        }
+    }
+
+    #[test]
+    fn preset_from_name() {
+        for preset in Preset::ALL {
+            assert_eq!(preset.name().parse::<Preset>(), Ok(*preset));
+        }
+
+        assert_eq!("little-robot".parse::<Preset>(), Ok(Preset::LittleRobot));
+        assert_eq!("extra terrestrial".parse::<Preset>(), Ok(Preset::ExtraTerrestrial));
+        assert_eq!("Robby".parse::<Preset>(), Err(VoiceError::UnknownPreset("Robby".to_owned())));
+    }
+
+    #[test]
+    fn builder_rejects_out_of_range() {
+        assert_eq!(Voice::builder().pitch(0).build(), Err(VoiceError::InvalidPitch(0)));
+        assert_eq!(Voice::builder().speed(0).build(), Err(VoiceError::InvalidSpeed(0)));
+        assert_eq!(Voice::builder().sample_rate(0).build(), Err(VoiceError::InvalidSampleRate(0)));
+        assert_eq!(Voice::builder().sample_rate(48000).build().unwrap().sample_rate(), 48000);
+    }
+}
@@ -0,0 +1,164 @@
+use crate::parser::{self, ParseError, Phoneme};
+use crate::reciter::{Dictionary, Reciter, ReciterError, RuleSet};
+use crate::renderer::{self, Renderer, Sample, Voice};
+
+#[derive(Debug)]
+pub enum Error {
+    Reciter(ReciterError),
+    Parse(ParseError)
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Reciter(err) => Some(err),
+            Error::Parse(err) => Some(err)
+        }
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::Reciter(err) => write!(f, "Could not convert text to phonemes ({})", err),
+            Error::Parse(err) => write!(f, "Could not parse phonemes ({})", err)
+        }
+    }
+}
+
+impl From<ReciterError> for Error {
+    fn from(err: ReciterError) -> Self {
+        Error::Reciter(err)
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(err: ParseError) -> Self {
+        Error::Parse(err)
+    }
+}
+
+/// The complete text-to-speech pipeline: reciter, parser and renderer.
+///
+/// The compatibility setting of the voice applies to all three stages.
+#[derive(Clone, Debug, Default)]
+pub struct Sam {
+    voice: Voice,
+    reciter: Reciter
+}
+
+impl Sam {
+    pub fn new(voice: Voice) -> Self {
+        Self {
+            reciter: Reciter::new().with_compatibility(voice.compatibility()),
+            voice
+        }
+    }
+
+    /// Convert text to phonemes using different rules, see [`Reciter::with_rules`].
+    pub fn with_rules(mut self, rules: RuleSet) -> Self {
+        self.reciter = self.reciter.with_rules(rules);
+        self
+    }
+
+    /// Pronounce the words in the dictionary as listed, see [`Reciter::with_dictionary`].
+    pub fn with_dictionary(mut self, dictionary: Dictionary) -> Self {
+        self.reciter = self.reciter.with_dictionary(dictionary);
+        self
+    }
+
+    /// Read numbers, amounts of money and times as words, see [`Reciter::with_normalization`].
+    pub fn with_normalization(mut self, normalize: bool) -> Self {
+        self.reciter = self.reciter.with_normalization(normalize);
+        self
+    }
+
+    pub fn voice(&self) -> &Voice {
+        &self.voice
+    }
+
+    /// Convert English text to phonemes.
+    pub fn phonemes(&self, text: &str) -> Result<Vec<Phoneme>, Error> {
+        let phonetic = self.reciter.text_to_phonemes(text)?;
+
+        self.phonetic_phonemes(&phonetic)
+    }
+
+    /// Parse input written in SAM's phonetic notation, such as "/HEHLOW5".
+    pub fn phonetic_phonemes(&self, phonetic: &str) -> Result<Vec<Phoneme>, Error> {
+        Ok(parser::parse_phonemes_with(phonetic, self.voice.compatibility())?)
+    }
+
+    /// Speak English text, returning unsigned 8 bit samples at the sample rate of the voice.
+    pub fn speak(&self, text: &str) -> Result<Vec<u8>, Error> {
+        Ok(renderer::render_with(&self.phonemes(text)?, &self.voice))
+    }
+
+    /// Speak input written in SAM's phonetic notation, returning unsigned 8 bit samples at the
+    /// sample rate of the voice.
+    pub fn speak_phonetic(&self, phonetic: &str) -> Result<Vec<u8>, Error> {
+        Ok(renderer::render_with(&self.phonetic_phonemes(phonetic)?, &self.voice))
+    }
+
+    /// Like [`Sam::speak`], but returns samples of another type, such as `i16` or `f32`.
+    pub fn speak_samples<S: Sample>(&self, text: &str) -> Result<Vec<S>, Error> {
+        Ok(renderer::render_samples(&self.phonemes(text)?, &self.voice))
+    }
+
+    /// Like [`Sam::speak_phonetic`], but returns samples of another type, such as `i16` or `f32`.
+    pub fn speak_phonetic_samples<S: Sample>(&self, phonetic: &str) -> Result<Vec<S>, Error> {
+        Ok(renderer::render_samples(&self.phonetic_phonemes(phonetic)?, &self.voice))
+    }
+
+    /// Like [`Sam::speak`], but returns a [`Renderer`] that produces the samples incrementally.
+    pub fn stream(&self, text: &str) -> Result<Renderer, Error> {
+        Ok(Renderer::new(&self.phonemes(text)?, &self.voice))
+    }
+
+    /// Like [`Sam::speak_phonetic`], but returns a [`Renderer`] that produces the samples
+    /// incrementally.
+    pub fn stream_phonetic(&self, phonetic: &str) -> Result<Renderer, Error> {
+        Ok(Renderer::new(&self.phonetic_phonemes(phonetic)?, &self.voice))
+    }
+}
+
+/// Speak English text with the given voice. See [`Sam::speak`].
+pub fn speak(text: &str, voice: &Voice) -> Result<Vec<u8>, Error> {
+    Sam::new(voice.clone()).speak(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn speak_matches_pipeline() {
+        let voice = Voice::default();
+
+        let phonetic = crate::reciter::text_to_phonemes("HELLO").unwrap();
+        let phonemes = parser::parse_phonemes(&phonetic).unwrap();
+        let expected = renderer::render_with(&phonemes, &voice);
+
+        assert_eq!(speak("HELLO", &voice).unwrap(), expected);
+        assert_eq!(Sam::new(voice.clone()).speak_phonetic(&phonetic).unwrap(), expected);
+        assert_eq!(Sam::new(voice).stream("HELLO").unwrap().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn nothing_to_say() {
+        assert_eq!(Sam::default().speak("").unwrap(), Vec::<u8>::new());
+        assert_eq!(Sam::default().speak_phonetic(" ").unwrap(), Vec::<u8>::new());
+        assert_eq!(speak(" ", &Voice::default()).unwrap(), Vec::<u8>::new());
+
+        let voice = Voice::builder().sample_rate(44100).build().unwrap();
+        assert!(Sam::new(voice).speak_phonetic_samples::<f32>(" ").unwrap().is_empty());
+    }
+
+    #[test]
+    fn reciter_error() {
+        let result = Sam::default().speak("`");
+
+        assert!(matches!(result, Err(Error::Reciter(ReciterError::BadPunctuation))));
+        assert!(std::error::Error::source(&result.unwrap_err()).is_some());
+    }
+}
@@ -0,0 +1,333 @@
+use crate::parser::PhonemeKind;
+use crate::renderer::TimelineEntry;
+
+/// A set of mouth shapes for animating speech.
+///
+/// Both sets classify the internal release states of plosives and affricates like the phoneme they
+/// belong to, and show the mouth at rest during pauses and the glottal stop.
+pub trait Viseme: Copy + Eq + std::fmt::Debug {
+    /// The closed mouth, shown during silence.
+    const REST: Self;
+
+    /// The mouth shape of a phoneme.
+    fn from_phoneme(kind: PhonemeKind) -> Self;
+
+    /// The conventional name of the mouth shape.
+    fn name(&self) -> &'static str;
+}
+
+/// The ten mouth shapes of Preston Blair's animation guide.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum PrestonBlair {
+    /// Open vowels, as in hot, bat and high
+    Ai,
+
+    /// Spread vowels, as in beet, bet and bird
+    E,
+
+    /// Rounded vowels, as in bought and slow
+    O,
+
+    /// Pursed vowels, as in crew and book
+    U,
+
+    /// Consonants that barely show on the lips, such as T, K, S and R
+    Etc,
+
+    /// L and TH, with the tongue against the teeth
+    L,
+
+    /// W, with pursed lips
+    Wq,
+
+    /// M, B and P, with closed lips
+    Mbp,
+
+    /// F and V, with the lower lip against the teeth
+    Fv,
+
+    /// Silence
+    Rest
+}
+
+impl PrestonBlair {
+    pub const ALL: &'static [PrestonBlair] = &[
+        PrestonBlair::Ai, PrestonBlair::E, PrestonBlair::O, PrestonBlair::U, PrestonBlair::Etc,
+        PrestonBlair::L, PrestonBlair::Wq, PrestonBlair::Mbp, PrestonBlair::Fv, PrestonBlair::Rest
+    ];
+}
+
+impl Viseme for PrestonBlair {
+    const REST: Self = PrestonBlair::Rest;
+
+    fn from_phoneme(kind: PhonemeKind) -> Self {
+        use PhonemeKind::*;
+
+        match kind.base() {
+            kind if kind.is_pause() => PrestonBlair::Rest,
+            Q => PrestonBlair::Rest,
+            Aa | Ae | Ah | Ax | Ay | Aw => PrestonBlair::Ai,
+            Iy | Ih | Ix | Eh | Ey | Er | Y | Yx => PrestonBlair::E,
+            Ao | Oh | Ow | Oy => PrestonBlair::O,
+            Uw | Ux | Uh => PrestonBlair::U,
+            W | Wx | Wh => PrestonBlair::Wq,
+            L | Lx | Ul | Th | Dh => PrestonBlair::L,
+            M | B | P | Um => PrestonBlair::Mbp,
+            F | V => PrestonBlair::Fv,
+            _ => PrestonBlair::Etc
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            PrestonBlair::Ai => "AI",
+            PrestonBlair::E => "E",
+            PrestonBlair::O => "O",
+            PrestonBlair::U => "U",
+            PrestonBlair::Etc => "etc",
+            PrestonBlair::L => "L",
+            PrestonBlair::Wq => "WQ",
+            PrestonBlair::Mbp => "MBP",
+            PrestonBlair::Fv => "FV",
+            PrestonBlair::Rest => "rest"
+        }
+    }
+}
+
+/// The fifteen visemes of the Oculus lip sync set, in the order of their indices.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Oculus {
+    /// Silence
+    Sil,
+
+    /// P*, B* and M*
+    Pp,
+
+    /// F* and V*
+    Ff,
+
+    /// TH and DH
+    Th,
+
+    /// T*, D* and DX
+    Dd,
+
+    /// K*, G*, NX and the aspirates
+    Kk,
+
+    /// CH, J*, SH and ZH
+    Ch,
+
+    /// S* and Z*
+    Ss,
+
+    /// N* and L*
+    Nn,
+
+    /// R* and ER
+    Rr,
+
+    /// Open vowels, as in hot, bat and but
+    Aa,
+
+    /// As in bet and made
+    E,
+
+    /// As in beet and bit
+    I,
+
+    /// As in bought and slow
+    O,
+
+    /// As in crew and book
+    U
+}
+
+impl Oculus {
+    pub const ALL: &'static [Oculus] = &[
+        Oculus::Sil, Oculus::Pp, Oculus::Ff, Oculus::Th, Oculus::Dd, Oculus::Kk, Oculus::Ch,
+        Oculus::Ss, Oculus::Nn, Oculus::Rr, Oculus::Aa, Oculus::E, Oculus::I, Oculus::O, Oculus::U
+    ];
+
+    /// The index of the viseme in the Oculus set, 0 for silence up to 14 for U.
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+impl Viseme for Oculus {
+    const REST: Self = Oculus::Sil;
+
+    fn from_phoneme(kind: PhonemeKind) -> Self {
+        use PhonemeKind::*;
+
+        match kind.base() {
+            kind if kind.is_pause() => Oculus::Sil,
+            Q => Oculus::Sil,
+            M | B | P | Um => Oculus::Pp,
+            F | V => Oculus::Ff,
+            Th | Dh => Oculus::Th,
+            T | D | Dx => Oculus::Dd,
+            K | Kx | G | Gx | Nx | SlashH | SlashX => Oculus::Kk,
+            Ch | J | Sh | Zh => Oculus::Ch,
+            S | Z => Oculus::Ss,
+            N | Un | L | Lx | Ul => Oculus::Nn,
+            R | Rx | Er => Oculus::Rr,
+            Aa | Ae | Ah | Ax | Ay | Aw => Oculus::Aa,
+            Eh | Ey => Oculus::E,
+            Iy | Ih | Ix | Y | Yx => Oculus::I,
+            Ao | Oh | Ow | Oy => Oculus::O,
+            Uw | Ux | Uh | W | Wx | Wh => Oculus::U,
+
+            // The release states have been replaced by the phoneme they belong to
+            _ => Oculus::Sil
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Oculus::Sil => "sil",
+            Oculus::Pp => "PP",
+            Oculus::Ff => "FF",
+            Oculus::Th => "TH",
+            Oculus::Dd => "DD",
+            Oculus::Kk => "kk",
+            Oculus::Ch => "CH",
+            Oculus::Ss => "SS",
+            Oculus::Nn => "nn",
+            Oculus::Rr => "RR",
+            Oculus::Aa => "aa",
+            Oculus::E => "E",
+            Oculus::I => "I",
+            Oculus::O => "O",
+            Oculus::U => "U"
+        }
+    }
+}
+
+impl std::fmt::Display for PrestonBlair {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl std::fmt::Display for Oculus {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// The moment the mouth changes to a new shape. A keyframe lasts until the next one.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Keyframe<V: Viseme> {
+    pub viseme: V,
+
+    /// The output sample the shape starts at.
+    pub sample: usize,
+
+    /// The start of the shape in seconds.
+    pub time: f64
+}
+
+/// Turn a phoneme timeline from the renderer into viseme keyframes.
+///
+/// Consecutive phonemes with the same shape are merged and phonemes without samples are skipped.
+/// The last keyframe returns the mouth to rest at the end of the output, unless it already is.
+pub fn keyframes<V: Viseme>(timeline: &[TimelineEntry], sample_rate: u32) -> Vec<Keyframe<V>> {
+    let mut keyframes: Vec<Keyframe<V>> = Vec::new();
+
+    let mut push = |viseme: V, sample: usize| {
+        if keyframes.last().is_some_and(|last| last.viseme == viseme) {
+            return;
+        }
+
+        keyframes.push(Keyframe {
+            viseme,
+            sample,
+            time: sample as f64 / sample_rate as f64
+        });
+    };
+
+    for entry in timeline.iter().filter(|entry| entry.end > entry.start) {
+        push(V::from_phoneme(entry.kind), entry.start);
+    }
+
+    if let Some(end) = timeline.iter().map(|entry| entry.end).max() {
+        push(V::REST, end);
+    }
+
+    keyframes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classification() {
+        assert_eq!(Oculus::from_phoneme(PhonemeKind::Pause), Oculus::Sil);
+        assert_eq!(Oculus::from_phoneme(PhonemeKind::PRelease2), Oculus::Pp);
+        assert_eq!(Oculus::from_phoneme(PhonemeKind::ChRelease), Oculus::Ch);
+        assert_eq!(Oculus::from_phoneme(PhonemeKind::Aa), Oculus::Aa);
+        assert_eq!(PrestonBlair::from_phoneme(PhonemeKind::Period), PrestonBlair::Rest);
+        assert_eq!(PrestonBlair::from_phoneme(PhonemeKind::M), PrestonBlair::Mbp);
+        assert_eq!(PrestonBlair::from_phoneme(PhonemeKind::V), PrestonBlair::Fv);
+
+        // Only pauses and the glottal stop show the mouth at rest
+        for kind in PhonemeKind::ALL {
+            let silent = kind.is_pause() || *kind == PhonemeKind::Q;
+
+            assert_eq!(Oculus::from_phoneme(*kind) == Oculus::Sil, silent, "{:?}", kind);
+            assert_eq!(PrestonBlair::from_phoneme(*kind) == PrestonBlair::Rest, silent, "{:?}", kind);
+        }
+
+        for (index, viseme) in Oculus::ALL.iter().enumerate() {
+            assert_eq!(viseme.index(), index);
+        }
+    }
+
+    #[test]
+    fn keyframes_from_timeline() {
+        let entry = |kind, start, end| TimelineEntry {
+            index: 0,
+            kind,
+            stress: 0,
+            start,
+            end
+        };
+
+        let timeline = [
+            entry(PhonemeKind::M, 0, 100),
+            entry(PhonemeKind::Aa, 100, 300),
+            entry(PhonemeKind::Yx, 300, 400),
+            entry(PhonemeKind::Iy, 400, 500),
+            entry(PhonemeKind::BRelease1, 500, 500),
+            entry(PhonemeKind::Period, 500, 600)
+        ];
+
+        let keyframes = keyframes::<Oculus>(&timeline, 100)
+            .into_iter()
+            .map(|keyframe| (keyframe.viseme, keyframe.sample, keyframe.time))
+            .collect::<Vec<_>>();
+
+        assert_eq!(keyframes, [
+            (Oculus::Pp, 0, 0.0),
+            (Oculus::Aa, 100, 1.0),
+            (Oculus::I, 300, 3.0),
+            (Oculus::Sil, 500, 5.0)
+        ]);
+    }
+
+    #[test]
+    fn keyframes_from_renderer() {
+        let phonemes = crate::parser::parse_phonemes("/HEHLOW5.").unwrap();
+        let voice = crate::renderer::Voice::default();
+        let (samples, timeline) = crate::renderer::render_with_timeline::<u8>(&phonemes, &voice);
+
+        let keyframes = keyframes::<PrestonBlair>(&timeline, voice.sample_rate());
+        let visemes = keyframes.iter().map(|keyframe| keyframe.viseme).collect::<Vec<_>>();
+
+        assert_eq!(visemes, [PrestonBlair::Etc, PrestonBlair::E, PrestonBlair::L, PrestonBlair::O, PrestonBlair::Wq, PrestonBlair::Rest]);
+        assert!(keyframes.last().unwrap().sample < samples.len());
+    }
+}
@@ -0,0 +1,190 @@
+use std::io::{self, Write};
+
+use crate::renderer::Sample;
+
+/// The sample format of the data chunk in a WAV file.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Format {
+    /// Unsigned 8 bit PCM, which is the native output format of the renderer.
+    Unsigned8,
+
+    /// Signed 16 bit PCM.
+    Signed16,
+
+    /// 32 bit IEEE floating point.
+    Float32
+}
+
+impl Format {
+    fn format_tag(&self) -> u16 {
+        match self {
+            Format::Unsigned8 | Format::Signed16 => 1, // WAVE_FORMAT_PCM
+            Format::Float32 => 3                       // WAVE_FORMAT_IEEE_FLOAT
+        }
+    }
+
+    fn bytes_per_sample(&self) -> u32 {
+        match self {
+            Format::Unsigned8 => 1,
+            Format::Signed16 => 2,
+            Format::Float32 => 4
+        }
+    }
+}
+
+/// Write mono samples as produced by the renderer to a RIFF/WAVE file, converting them to the
+/// requested format.
+pub fn write_wav<W: Write, S: Sample>(mut writer: W, samples: &[S], sample_rate: u32, format: Format) -> io::Result<()> {
+    let bytes_per_sample = format.bytes_per_sample();
+    let data_size = samples.len() as u32 * bytes_per_sample;
+
+    // Formats other than PCM have an extended fmt chunk and need a fact chunk
+    let is_pcm = format.format_tag() == 1;
+    let fmt_size: u32 = if is_pcm { 16 } else { 18 };
+    let fact_size: u32 = if is_pcm { 0 } else { 12 };
+
+    // Chunks are padded to an even size
+    let padding = data_size & 1;
+
+    // RIFF header
+    writer.write_all(b"RIFF")?;
+    writer.write_all(&(4 + (8 + fmt_size) + fact_size + (8 + data_size + padding)).to_le_bytes())?;
+    writer.write_all(b"WAVE")?;
+
+    // fmt chunk
+    writer.write_all(b"fmt ")?;
+    writer.write_all(&fmt_size.to_le_bytes())?;
+    writer.write_all(&format.format_tag().to_le_bytes())?;
+    writer.write_all(&1_u16.to_le_bytes())?; // Mono
+    writer.write_all(&sample_rate.to_le_bytes())?;
+    writer.write_all(&(sample_rate * bytes_per_sample).to_le_bytes())?; // Byte rate
+    writer.write_all(&(bytes_per_sample as u16).to_le_bytes())?; // Block align
+    writer.write_all(&(bytes_per_sample as u16 * 8).to_le_bytes())?; // Bits per sample
+
+    if !is_pcm {
+        writer.write_all(&0_u16.to_le_bytes())?; // Extension size
+
+        // fact chunk
+        writer.write_all(b"fact")?;
+        writer.write_all(&4_u32.to_le_bytes())?;
+        writer.write_all(&(samples.len() as u32).to_le_bytes())?;
+    }
+
+    // data chunk
+    writer.write_all(b"data")?;
+    writer.write_all(&data_size.to_le_bytes())?;
+
+    write_samples(&mut writer, samples, format)?;
+
+    if padding != 0 {
+        writer.write_all(&[0])?;
+    }
+
+    Ok(())
+}
+
+/// Write mono samples without a header, in the little endian layout of the data chunk of a WAV
+/// file with the given format. Samples beyond full scale are clipped in every format.
+pub fn write_samples<W: Write, S: Sample>(mut writer: W, samples: &[S], format: Format) -> io::Result<()> {
+    match format {
+        Format::Unsigned8 => for sample in samples {
+            writer.write_all(&[u8::from_float(sample.to_float())])?;
+        },
+        Format::Signed16 => for sample in samples {
+            writer.write_all(&i16::from_float(sample.to_float()).to_le_bytes())?;
+        },
+        Format::Float32 => for sample in samples {
+            writer.write_all(&sample.to_float().clamp(-1.0, 1.0).to_le_bytes())?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn u16_at(data: &[u8], offset: usize) -> u16 {
+        u16::from_le_bytes(data[offset..offset + 2].try_into().unwrap())
+    }
+
+    fn u32_at(data: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn unsigned8() {
+        let mut data = Vec::new();
+        write_wav(&mut data, &[0x80_u8, 0xff, 0x00], 22050, Format::Unsigned8).unwrap();
+
+        assert_eq!(&data[0..4], b"RIFF");
+        assert_eq!(u32_at(&data, 4) as usize, data.len() - 8);
+        assert_eq!(&data[8..16], b"WAVEfmt ");
+        assert_eq!(u32_at(&data, 16), 16);
+        assert_eq!(u16_at(&data, 20), 1);
+        assert_eq!(u16_at(&data, 22), 1);
+        assert_eq!(u32_at(&data, 24), 22050);
+        assert_eq!(u32_at(&data, 28), 22050);
+        assert_eq!(u16_at(&data, 32), 1);
+        assert_eq!(u16_at(&data, 34), 8);
+        assert_eq!(&data[36..40], b"data");
+        assert_eq!(u32_at(&data, 40), 3);
+        assert_eq!(&data[44..47], &[0x80, 0xff, 0x00]);
+
+        // Padding byte
+        assert_eq!(data.len(), 48);
+    }
+
+    #[test]
+    fn signed16() {
+        let mut data = Vec::new();
+        write_wav(&mut data, &[0x80_u8, 0xff, 0x00], 22050, Format::Signed16).unwrap();
+
+        assert_eq!(u32_at(&data, 28), 44100);
+        assert_eq!(u16_at(&data, 32), 2);
+        assert_eq!(u16_at(&data, 34), 16);
+        assert_eq!(u32_at(&data, 40), 6);
+        assert_eq!(u16_at(&data, 44) as i16, 0);
+        assert_eq!(u16_at(&data, 46) as i16, 0x7f00);
+        assert_eq!(u16_at(&data, 48) as i16, -0x8000);
+        assert_eq!(data.len(), 50);
+    }
+
+    #[test]
+    fn float32() {
+        let mut data = Vec::new();
+        write_wav(&mut data, &[0x80_u8, 0x00], 22050, Format::Float32).unwrap();
+
+        assert_eq!(u32_at(&data, 4) as usize, data.len() - 8);
+        assert_eq!(u32_at(&data, 16), 18);
+        assert_eq!(u16_at(&data, 20), 3);
+        assert_eq!(u16_at(&data, 34), 32);
+        assert_eq!(&data[38..42], b"fact");
+        assert_eq!(u32_at(&data, 46), 2);
+        assert_eq!(&data[50..54], b"data");
+        assert_eq!(u32_at(&data, 54), 8);
+        assert_eq!(f32::from_le_bytes(data[58..62].try_into().unwrap()), 0.0);
+        assert_eq!(f32::from_le_bytes(data[62..66].try_into().unwrap()), -1.0);
+
+        // Floats beyond full scale, as left by a gain above one, are clipped
+        data.clear();
+        write_samples(&mut data, &[4.0_f32, -1.5, 0.25], Format::Float32).unwrap();
+        let floats: Vec<f32> = data.chunks(4).map(|bytes| f32::from_le_bytes(bytes.try_into().unwrap())).collect();
+        assert_eq!(floats, [1.0, -1.0, 0.25]);
+    }
+
+    #[test]
+    fn converts_sample_types() {
+        let mut expected = Vec::new();
+        write_wav(&mut expected, &[0x80_u8, 0xff, 0x00], 22050, Format::Signed16).unwrap();
+
+        let mut data = Vec::new();
+        write_wav(&mut data, &[0_i16, 0x7f00, -0x8000], 22050, Format::Signed16).unwrap();
+        assert_eq!(data, expected);
+
+        data.clear();
+        write_wav(&mut data, &[0.0_f32, 127.0 / 128.0, -1.0], 22050, Format::Signed16).unwrap();
+        assert_eq!(data, expected);
+    }
+}
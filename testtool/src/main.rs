@@ -109,7 +109,7 @@ impl Output {
         // Map sam-js' (phoneme index, duration, stress) triple to Phoneme instances
         let expected = self.parsed.iter().map(|&(index, length, stress)| {
             rustsam::parser::Phoneme {
-                index: index as usize,
+                kind: rustsam::parser::PhonemeKind::from_index(index as usize).expect("Invalid phoneme index in test data"),
                 length,
                 stress
             }
@@ -122,7 +122,7 @@ impl Output {
         // Map sam-js' (phoneme index, duration, stress) triple to Phoneme instances
         let phonemes = self.parsed.iter().map(|&(index, length, stress)| {
             rustsam::parser::Phoneme {
-                index: index as usize,
+                kind: rustsam::parser::PhonemeKind::from_index(index as usize).expect("Invalid phoneme index in test data"),
                 length,
                 stress
             }